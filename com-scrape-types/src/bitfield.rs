@@ -0,0 +1,125 @@
+/// Storage for a run of adjacent C bitfields.
+///
+/// `com-scrape` groups consecutive bitfields in a struct into a single `BitfieldUnit` spanning the
+/// `N` bytes they occupy, and generates getter and setter methods on the struct which access
+/// individual bitfields via [`get`](Self::get) and [`set`](Self::set).
+///
+/// Bit offsets are relative to the start of the unit and follow the C ABI bit order for the
+/// target, i.e. bit 0 is the least significant bit of the first byte on little-endian targets and
+/// the most significant bit of the first byte on big-endian targets.
+#[repr(transparent)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct BitfieldUnit<const N: usize>([u8; N]);
+
+impl<const N: usize> Default for BitfieldUnit<N> {
+    #[inline]
+    fn default() -> Self {
+        BitfieldUnit([0; N])
+    }
+}
+
+impl<const N: usize> BitfieldUnit<N> {
+    /// Creates a `BitfieldUnit` from its raw storage.
+    #[inline]
+    pub const fn new(storage: [u8; N]) -> Self {
+        BitfieldUnit(storage)
+    }
+
+    /// Returns the raw storage of the unit.
+    #[inline]
+    pub const fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    #[inline]
+    fn bit_mask(index: usize) -> u8 {
+        let bit = if cfg!(target_endian = "big") {
+            7 - index % 8
+        } else {
+            index % 8
+        };
+
+        1 << bit
+    }
+
+    /// Gets the value of the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds for the unit.
+    #[inline]
+    pub fn get_bit(&self, index: usize) -> bool {
+        self.0[index / 8] & Self::bit_mask(index) != 0
+    }
+
+    /// Sets the value of the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds for the unit.
+    #[inline]
+    pub fn set_bit(&mut self, index: usize, value: bool) {
+        let mask = Self::bit_mask(index);
+        if value {
+            self.0[index / 8] |= mask;
+        } else {
+            self.0[index / 8] &= !mask;
+        }
+    }
+
+    /// Gets the `width`-bit unsigned value starting at bit `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is greater than 64 or if the bits are out of bounds for the unit.
+    #[inline]
+    pub fn get(&self, offset: usize, width: usize) -> u64 {
+        assert!(width <= 64);
+
+        let mut value = 0;
+        for i in 0..width {
+            if self.get_bit(offset + i) {
+                let index = if cfg!(target_endian = "big") {
+                    width - 1 - i
+                } else {
+                    i
+                };
+                value |= 1 << index;
+            }
+        }
+
+        value
+    }
+
+    /// Gets the `width`-bit signed value starting at bit `offset`, sign-extending it to 64 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero or greater than 64, or if the bits are out of bounds for the unit.
+    #[inline]
+    pub fn get_signed(&self, offset: usize, width: usize) -> i64 {
+        assert!(width > 0);
+
+        let shift = 64 - width;
+        ((self.get(offset, width) << shift) as i64) >> shift
+    }
+
+    /// Sets the `width` bits starting at bit `offset` to the low `width` bits of `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is greater than 64 or if the bits are out of bounds for the unit.
+    #[inline]
+    pub fn set(&mut self, offset: usize, width: usize, value: u64) {
+        assert!(width <= 64);
+
+        for i in 0..width {
+            let index = if cfg!(target_endian = "big") {
+                width - 1 - i
+            } else {
+                i
+            };
+            self.set_bit(offset + i, value & (1 << index) != 0);
+        }
+    }
+}
@@ -1,5 +1,5 @@
+use std::marker::PhantomData;
 use std::ops::Deref;
-use std::ptr::addr_of;
 use std::sync::Arc;
 
 use super::{ComPtr, ComRef, Guid, Interface};
@@ -28,23 +28,60 @@ macro_rules! offset_of {
 /// for additional wrapper types to implement it in the future.
 pub trait Wrapper<C: Class + ?Sized> {
     /// Given a pointer to an object's header, returns a pointer to the object itself.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to the header of a live object allocated by this wrapper.
     unsafe fn data_from_header(ptr: *mut Header<C>) -> *mut C;
 
     /// Given a pointer to an object, returns a pointer to the object's header.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a live object allocated by this wrapper.
     unsafe fn header_from_data(ptr: *mut C) -> *mut Header<C>;
 
     /// Increments the reference count of an object and returns the resulting count.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a live object allocated by this wrapper.
     unsafe fn add_ref(ptr: *mut C) -> usize;
 
     /// Decrements the reference count of an object and returns the resulting count.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a live object allocated by this wrapper, and the caller must own one of
+    /// its references. If the count reaches zero, the object is destroyed and `ptr` must no longer
+    /// be used.
     unsafe fn release(ptr: *mut C) -> usize;
 }
 
+/// A compile-time offset within an object header.
+///
+/// Offsets are passed to [`Construct`] as types rather than as `const` parameters, since the offset
+/// of a base class object depends on the sizes of the (generic) interfaces preceding it in the
+/// header.
+pub trait Offset {
+    const OFFSET: isize;
+}
+
+/// The offset of the `INDEX`th base class object within the header of the interface list `L`.
+///
+/// Implements [`Offset`] for each interface in `L`.
+pub struct HeaderOffset<L, const INDEX: usize>(PhantomData<L>);
+
 /// Generates the virtual table and base class object for a given class and interface.
 ///
 /// The `W` parameter is the wrapper type which provides helper functionality for reference
-/// counting and class layout. The `OFFSET` parameter is the offset, within the header, at which
-/// the constructed base class object will be located.
+/// counting and class layout. The `O` parameter provides the offset ([`Offset::OFFSET`]), within
+/// the header, at which the constructed base class object will be located.
+///
+/// For interfaces with multiple base classes, the generated base class object also contains a
+/// subobject (with its own virtual table) for each secondary base class. The virtual methods of a
+/// secondary base class subobject located `n` bytes into the base class object recover the header
+/// by subtracting `O::OFFSET + n` from their `this` pointer.
 ///
 /// This trait is used in the implementations of [`MakeHeader`] for tuples of interfaces. Interface
 /// types generated by `com-scrape` will implement this trait.
@@ -54,7 +91,7 @@ pub trait Wrapper<C: Class + ?Sized> {
 /// When `I::OBJ` is reinterpreted as `*const I::Vtbl` (see the
 /// [safety documentation](Interface#safety) for [`Interface`]), it must be a valid pointer to an
 /// instance of `I::Vtbl`.
-pub unsafe trait Construct<C: Class, W: Wrapper<C>, const OFFSET: isize>: Interface {
+pub unsafe trait Construct<C: Class, W: Wrapper<C>, O: Offset>: Interface {
     /// The generated base class object.
     const OBJ: Self;
 }
@@ -121,18 +158,28 @@ pub trait Class {
 /// Convenience alias for getting the object header of a [`Class`].
 pub type Header<C> = <<C as Class>::Interfaces as InterfaceList>::Header;
 
+macro_rules! header_offset {
+    ($header:ident, [$($interface:ident),*], $index:tt) => {
+        impl<$($interface: Interface),*> Offset for HeaderOffset<($($interface,)*), $index> {
+            const OFFSET: isize = unsafe { offset_of!($header<$($interface),*>, $index) };
+        }
+    };
+}
+
 macro_rules! interface_list {
     ($header:ident, $($interface:ident $index:tt),*) => {
         #[repr(C)]
         pub struct $header<$($interface),*>($($interface),*);
 
+        interface_list!(@offsets $header, [$($interface),*], [$($index),*]);
+
         unsafe impl<$($interface: Interface),*> InterfaceList for ($($interface,)*) {
             type Header = $header<$($interface),*>;
 
             fn query(iid: &Guid) -> Option<isize> {
                 $(
-                    if $interface::inherits(iid) {
-                        return Some($index * std::mem::size_of::<*mut ()>() as isize);
+                    if let Some(offset) = $interface::base_offset(iid) {
+                        return Some(<HeaderOffset<Self, $index> as Offset>::OFFSET + offset);
                     }
                 )*
 
@@ -144,11 +191,14 @@ macro_rules! interface_list {
         where
             C: Class,
             W: Wrapper<C>,
-            $($interface: Construct<C, W, { $index * std::mem::size_of::<*mut ()>() as isize }>,)*
+            $($interface: Construct<C, W, HeaderOffset<Self, $index>>,)*
         {
             const HEADER: Self::Header = $header($($interface::OBJ),*);
         }
-    }
+    };
+    (@offsets $header:ident, $interfaces:tt, [$($index:tt),*]) => {
+        $(header_offset!($header, $interfaces, $index);)*
+    };
 }
 
 interface_list!(Header1, I0 0);
@@ -19,19 +19,19 @@
 //! should suffice in the majority of situations:
 //!
 //! 1. When passing an interface pointer as a function parameter, use [`ComPtr::as_ptr`] to obtain a
-//! raw pointer from a [`ComPtr`], or use [`ComRef::as_ptr`] to obtain a raw pointer from a
-//! [`ComRef`].
+//!    raw pointer from a [`ComPtr`], or use [`ComRef::as_ptr`] to obtain a raw pointer from a
+//!    [`ComRef`].
 //!
 //! 2. When receiving an interface pointer as the return value of a function (or via an out
-//! parameter), always use [`ComPtr::from_raw`] to obtain a [`ComPtr`] from the raw pointer.
+//!    parameter), always use [`ComPtr::from_raw`] to obtain a [`ComPtr`] from the raw pointer.
 //!
 //! 3. When receiving an interface pointer as a function parameter, always use
-//! [`ComRef::from_raw`] to obtain a [`ComRef`] from the raw pointer. If the received interface
-//! pointer will be stored beyond the duration of the current function, use
-//! [`ComRef::to_com_ptr`] to upgrade the [`ComRef`] to a [`ComPtr`].
+//!    [`ComRef::from_raw`] to obtain a [`ComRef`] from the raw pointer. If the received interface
+//!    pointer will be stored beyond the duration of the current function, use
+//!    [`ComRef::to_com_ptr`] to upgrade the [`ComRef`] to a [`ComPtr`].
 //!
 //! 4. When returning an interface pointer from a function (or when returning it via an out
-//! parameter), always use [`ComPtr::into_raw`] to obtain a raw pointer from a [`ComPtr`].
+//!    parameter), always use [`ComPtr::into_raw`] to obtain a raw pointer from a [`ComPtr`].
 //!
 //! [rules]: https://learn.microsoft.com/en-us/windows/win32/com/rules-for-managing-reference-counts
 //!
@@ -83,15 +83,20 @@
 //! ptr.another_method();
 //! ```
 
+mod bitfield;
 mod class;
 mod ptr;
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::unnecessary_cast)]
 mod tests;
 
 use std::ffi::c_void;
 
-pub use class::{Class, ComWrapper, Construct, Header, InterfaceList, MakeHeader, Wrapper};
+pub use bitfield::BitfieldUnit;
+pub use class::{
+    Class, ComWrapper, Construct, Header, HeaderOffset, InterfaceList, MakeHeader, Offset, Wrapper,
+};
 pub use ptr::{ComPtr, ComRef, SmartPtr};
 
 /// A 16-byte unique identifier for a COM interface.
@@ -109,12 +114,26 @@ pub trait Unknown {
     /// Checks if an object implements the interface corresponding to the given GUID, and if so,
     /// returns a corresponding interface pointer for the object and increments the object's
     /// reference count.
+    ///
+    /// # Safety
+    ///
+    /// `this` must be a valid pointer to a live object implementing this interface.
     unsafe fn query_interface(this: *mut Self, iid: &Guid) -> Option<*mut c_void>;
 
     /// Increments an object's reference count and returns the resulting count.
+    ///
+    /// # Safety
+    ///
+    /// `this` must be a valid pointer to a live object implementing this interface.
     unsafe fn add_ref(this: *mut Self) -> usize;
 
     /// Decrements an object's reference count and returns the resulting count.
+    ///
+    /// # Safety
+    ///
+    /// `this` must be a valid pointer to a live object implementing this interface, and the caller
+    /// must own one of its references. If the count reaches zero, the object is destroyed and
+    /// `this` must no longer be used.
     unsafe fn release(this: *mut Self) -> usize;
 }
 
@@ -122,12 +141,15 @@ pub trait Unknown {
 ///
 /// # Safety
 ///
-/// If a type `I` implements `Interface`, it must have the same layout as the pointer type
-/// `*const I::Vtbl`.
+/// If a type `I` implements `Interface`, its layout must begin with the pointer type
+/// `*const I::Vtbl`. For interfaces with a single base class (or none), `I` must have the same
+/// layout as `*const I::Vtbl`; interfaces with multiple base classes are followed by a base class
+/// subobject for each additional base.
 ///
-/// If `I::inherits(J::IID)` returns `true`, then the layout of `J::Vtbl` must be a prefix of the
-/// layout of `I::Vtbl`, i.e. a valid pointer to an instance of `I::Vtbl` must also be valid
-/// pointer to an instance of `J::Vtbl`.
+/// If `I::base_offset(J::IID)` returns `Some(offset)`, then whenever `ptr: *mut I` points to a
+/// valid instance of `I`, `(ptr as *mut u8).offset(offset) as *mut J` must point to a valid
+/// instance of `J`. In particular, if `offset` is zero, then the layout of `J::Vtbl` must be a
+/// prefix of the layout of `I::Vtbl`.
 pub unsafe trait Interface: Unknown {
     /// The type of the virtual method table for this interface.
     type Vtbl;
@@ -140,12 +162,30 @@ pub unsafe trait Interface: Unknown {
     ///
     /// Note that this has safety implications; see the top-level documentation for [`Interface`].
     fn inherits(iid: &Guid) -> bool;
+
+    /// If this interface transitively inherits from the interface identified by `iid`, returns
+    /// the offset in bytes of the corresponding base class subobject within `Self`.
+    ///
+    /// The default implementation returns `Some(0)` whenever [`inherits`](Self::inherits) returns
+    /// `true`, which is correct for interfaces which only ever inherit along a single chain of
+    /// base classes.
+    ///
+    /// Note that this has safety implications; see the top-level documentation for [`Interface`].
+    fn base_offset(iid: &Guid) -> Option<isize> {
+        if Self::inherits(iid) {
+            Some(0)
+        } else {
+            None
+        }
+    }
 }
 
 /// Represents the "is-a" relationship for interfaces.
 ///
-/// If interface `I` implements `Inherits<J>`, it is valid to cast a pointer of type `*mut I` to a
-/// pointer of type `*mut J` and to call any of `J`'s methods via that pointer.
+/// If interface `I` implements `Inherits<J>`, it is valid to offset a pointer of type `*mut I` by
+/// [`OFFSET`](Inherits::OFFSET) bytes, cast it to a pointer of type `*mut J`, and call any of
+/// `J`'s methods via that pointer. The offset is zero unless `J` is reached through a secondary
+/// base class of an interface with multiple base classes.
 ///
 /// The `Inherits` relation should be reflexive and transitive, i.e. `I: Inherits<I>` should be
 /// true for any type `I`, and if `I: Inherits<J>` and `J: Inherits<K>` are true, then
@@ -154,12 +194,15 @@ pub unsafe trait Interface: Unknown {
 /// # Safety
 ///
 /// [`Interface`] is a supertrait of `Inherits`, so all of `Interface`'s safety requirements also
-/// apply to `Inherits`. In particular, if `I` implements `Inherits`, it must have the same layout
-/// as the pointer type `*const I::Vtbl`.
+/// apply to `Inherits`. In particular, if `I` implements `Inherits`, its layout must begin with the
+/// pointer type `*const I::Vtbl`.
 ///
-/// If `I` implements `Inherits<J>`, then the layout of `J::Vtbl` must be a prefix of the layout of
-/// `I::Vtbl`, i.e. a valid pointer to an instance of `I::Vtbl` must also be a valid pointer to an
-/// instance of `J::Vtbl`.
-pub unsafe trait Inherits<I: Interface>: Interface {}
+/// If `I` implements `Inherits<J>`, then whenever `ptr: *mut I` points to a valid instance of `I`,
+/// `(ptr as *mut u8).offset(<I as Inherits<J>>::OFFSET) as *mut J` must point to a valid instance
+/// of `J`.
+pub unsafe trait Inherits<I: Interface>: Interface {
+    /// The offset in bytes of the `I` base class subobject within `Self`.
+    const OFFSET: isize = 0;
+}
 
 unsafe impl<I: Interface> Inherits<I> for I {}
@@ -62,10 +62,7 @@ impl<'a, I: Interface> Copy for ComRef<'a, I> {}
 impl<'a, I: Interface> Clone for ComRef<'a, I> {
     #[inline]
     fn clone(&self) -> ComRef<'a, I> {
-        ComRef {
-            ptr: self.ptr,
-            _marker: PhantomData,
-        }
+        *self
     }
 }
 
@@ -75,7 +72,7 @@ impl<'a, I: Interface> ComRef<'a, I> {
     /// Does not perform any reference counting operations.
     #[inline]
     pub fn as_ptr(&self) -> *mut I {
-        self.ptr.as_ptr() as *mut I
+        self.ptr.as_ptr()
     }
 
     /// Creates a `ComRef` from a raw interface pointer if the pointer is non-null.
@@ -138,7 +135,10 @@ impl<'a, I: Interface> ComRef<'a, I> {
     where
         I: Inherits<J>,
     {
-        unsafe { ComRef::from_raw_unchecked(self.as_ptr() as *mut J) }
+        unsafe {
+            let ptr = (self.as_ptr() as *mut u8).offset(<I as Inherits<J>>::OFFSET);
+            ComRef::from_raw_unchecked(ptr as *mut J)
+        }
     }
 
     /// Attempts to cast from one interface to another, returning a [`ComPtr`] if successful.
@@ -207,7 +207,7 @@ impl<I: Interface> ComPtr<I> {
     /// Does not perform any reference counting operations.
     #[inline]
     pub fn as_ptr(&self) -> *mut I {
-        self.ptr.as_ptr() as *mut I
+        self.ptr.as_ptr()
     }
 
     /// Creates a `ComPtr` from a raw interface pointer if the pointer is non-null.
@@ -275,7 +275,10 @@ impl<I: Interface> ComPtr<I> {
     where
         I: Inherits<J>,
     {
-        unsafe { ComPtr::from_raw_unchecked(self.into_raw() as *mut J) }
+        unsafe {
+            let ptr = (self.into_raw() as *mut u8).offset(<I as Inherits<J>>::OFFSET);
+            ComPtr::from_raw_unchecked(ptr as *mut J)
+        }
     }
 
     /// Attempts to cast from one interface to another, returning another [`ComPtr`] if successful.
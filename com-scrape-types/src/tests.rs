@@ -53,12 +53,13 @@ unsafe impl Interface for IUnknown {
 }
 
 impl IUnknown {
-    pub const fn make_vtbl<C, W, const OFFSET: isize>() -> IUnknownVtbl
+    pub const fn make_vtbl<C, W, O, const ADJUST: isize>() -> IUnknownVtbl
     where
         C: Class,
         W: Wrapper<C>,
+        O: Offset,
     {
-        unsafe extern "system" fn query_interface<C, W, const OFFSET: isize>(
+        unsafe extern "system" fn query_interface<C, W, O, const ADJUST: isize>(
             this: *mut IUnknown,
             _iid: *const Guid,
             obj: *mut *mut c_void,
@@ -66,8 +67,9 @@ impl IUnknown {
         where
             C: Class,
             W: Wrapper<C>,
+            O: Offset,
         {
-            let header_ptr = (this as *mut u8).offset(-OFFSET) as *mut Header<C>;
+            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;
             if let Some(result) = C::Interfaces::query(&*(_iid as *const Guid)) {
                 let ptr = W::data_from_header(header_ptr);
                 W::add_ref(ptr);
@@ -80,41 +82,48 @@ impl IUnknown {
             }
         }
 
-        unsafe extern "system" fn add_ref<C, W, const OFFSET: isize>(this: *mut IUnknown) -> c_ulong
+        unsafe extern "system" fn add_ref<C, W, O, const ADJUST: isize>(
+            this: *mut IUnknown,
+        ) -> c_ulong
         where
             C: Class,
             W: Wrapper<C>,
+            O: Offset,
         {
-            let header_ptr = (this as *mut u8).offset(-OFFSET) as *mut Header<C>;
+            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;
             let ptr = W::data_from_header(header_ptr);
             W::add_ref(ptr) as c_ulong
         }
 
-        unsafe extern "system" fn release<C, W, const OFFSET: isize>(this: *mut IUnknown) -> c_ulong
+        unsafe extern "system" fn release<C, W, O, const ADJUST: isize>(
+            this: *mut IUnknown,
+        ) -> c_ulong
         where
             C: Class,
             W: Wrapper<C>,
+            O: Offset,
         {
-            let header_ptr = (this as *mut u8).offset(-OFFSET) as *mut Header<C>;
+            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;
             let ptr = W::data_from_header(header_ptr);
             W::release(ptr) as c_ulong
         }
 
         IUnknownVtbl {
-            query_interface: query_interface::<C, W, OFFSET>,
-            add_ref: add_ref::<C, W, OFFSET>,
-            release: release::<C, W, OFFSET>,
+            query_interface: query_interface::<C, W, O, ADJUST>,
+            add_ref: add_ref::<C, W, O, ADJUST>,
+            release: release::<C, W, O, ADJUST>,
         }
     }
 }
 
-unsafe impl<C, W, const OFFSET: isize> Construct<C, W, OFFSET> for IUnknown
+unsafe impl<C, W, O> Construct<C, W, O> for IUnknown
 where
     C: Class,
     W: Wrapper<C>,
+    O: Offset,
 {
     const OBJ: IUnknown = IUnknown {
-        vtbl: &Self::make_vtbl::<C, W, OFFSET>(),
+        vtbl: &Self::make_vtbl::<C, W, O, 0>(),
     };
 }
 
@@ -140,7 +149,8 @@ where
 {
     fn my_method(&self) -> u32 {
         unsafe {
-            let ptr = self.ptr() as *mut IMyInterface;
+            let ptr = (self.ptr() as *mut u8).offset(<P::Target as Inherits<IMyInterface>>::OFFSET)
+                as *mut IMyInterface;
             ((*(*ptr).vtbl).my_method)(ptr)
         }
     }
@@ -173,37 +183,40 @@ unsafe impl Interface for IMyInterface {
 unsafe impl Inherits<IUnknown> for IMyInterface {}
 
 impl IMyInterface {
-    pub const fn make_vtbl<C, W, const OFFSET: isize>() -> IMyInterfaceVtbl
+    pub const fn make_vtbl<C, W, O, const ADJUST: isize>() -> IMyInterfaceVtbl
     where
         C: IMyInterfaceTrait + Class,
         W: Wrapper<C>,
+        O: Offset,
     {
-        unsafe extern "system" fn my_method<C, W, const OFFSET: isize>(
+        unsafe extern "system" fn my_method<C, W, O, const ADJUST: isize>(
             this: *mut IMyInterface,
         ) -> u32
         where
             C: IMyInterfaceTrait + Class,
             W: Wrapper<C>,
+            O: Offset,
         {
-            let header_ptr = (this as *mut u8).offset(-OFFSET) as *mut Header<C>;
+            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;
             let ptr = W::data_from_header(header_ptr);
             (*ptr).my_method()
         }
 
         IMyInterfaceVtbl {
-            base: IUnknown::make_vtbl::<C, W, OFFSET>(),
-            my_method: my_method::<C, W, OFFSET>,
+            base: IUnknown::make_vtbl::<C, W, O, ADJUST>(),
+            my_method: my_method::<C, W, O, ADJUST>,
         }
     }
 }
 
-unsafe impl<C, W, const OFFSET: isize> Construct<C, W, OFFSET> for IMyInterface
+unsafe impl<C, W, O> Construct<C, W, O> for IMyInterface
 where
     C: IMyInterfaceTrait + Class,
     W: Wrapper<C>,
+    O: Offset,
 {
     const OBJ: IMyInterface = IMyInterface {
-        vtbl: &Self::make_vtbl::<C, W, OFFSET>(),
+        vtbl: &Self::make_vtbl::<C, W, O, 0>(),
     };
 }
 
@@ -229,7 +242,9 @@ where
 {
     fn other_method(&self) -> u32 {
         unsafe {
-            let ptr = self.ptr() as *mut IOtherInterface;
+            let ptr = (self.ptr() as *mut u8)
+                .offset(<P::Target as Inherits<IOtherInterface>>::OFFSET)
+                as *mut IOtherInterface;
             ((*(*ptr).vtbl).other_method)(ptr)
         }
     }
@@ -262,37 +277,40 @@ unsafe impl Interface for IOtherInterface {
 unsafe impl Inherits<IUnknown> for IOtherInterface {}
 
 impl IOtherInterface {
-    pub const fn make_vtbl<C, W, const OFFSET: isize>() -> IOtherInterfaceVtbl
+    pub const fn make_vtbl<C, W, O, const ADJUST: isize>() -> IOtherInterfaceVtbl
     where
         C: IOtherInterfaceTrait + Class,
         W: Wrapper<C>,
+        O: Offset,
     {
-        unsafe extern "system" fn other_method<C, W, const OFFSET: isize>(
+        unsafe extern "system" fn other_method<C, W, O, const ADJUST: isize>(
             this: *mut IOtherInterface,
         ) -> u32
         where
             C: IOtherInterfaceTrait + Class,
             W: Wrapper<C>,
+            O: Offset,
         {
-            let header_ptr = (this as *mut u8).offset(-OFFSET) as *mut Header<C>;
+            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;
             let ptr = W::data_from_header(header_ptr);
             (*ptr).other_method()
         }
 
         IOtherInterfaceVtbl {
-            base: IUnknown::make_vtbl::<C, W, OFFSET>(),
-            other_method: other_method::<C, W, OFFSET>,
+            base: IUnknown::make_vtbl::<C, W, O, ADJUST>(),
+            other_method: other_method::<C, W, O, ADJUST>,
         }
     }
 }
 
-unsafe impl<C, W, const OFFSET: isize> Construct<C, W, OFFSET> for IOtherInterface
+unsafe impl<C, W, O> Construct<C, W, O> for IOtherInterface
 where
     C: IOtherInterfaceTrait + Class,
     W: Wrapper<C>,
+    O: Offset,
 {
     const OBJ: IOtherInterface = IOtherInterface {
-        vtbl: &Self::make_vtbl::<C, W, OFFSET>(),
+        vtbl: &Self::make_vtbl::<C, W, O, 0>(),
     };
 }
 
@@ -489,3 +507,274 @@ fn com_wrapper() {
     drop(com_ptr_4);
     assert_eq!(dropped.get(), true);
 }
+
+#[repr(C)]
+struct IMultiInterface {
+    vtbl: *const IMultiInterfaceVtbl,
+    __base1: IOtherInterface,
+}
+
+#[repr(C)]
+struct IMultiInterfaceVtbl {
+    base: IMyInterfaceVtbl,
+    multi_method: unsafe extern "system" fn(this: *mut IMultiInterface) -> u32,
+}
+
+trait IMultiInterfaceTrait: IMyInterfaceTrait + IOtherInterfaceTrait {
+    fn multi_method(&self) -> u32;
+}
+
+impl<P> IMultiInterfaceTrait for P
+where
+    P: SmartPtr,
+    P::Target: Inherits<IMultiInterface>,
+    P::Target: Inherits<IMyInterface>,
+    P::Target: Inherits<IOtherInterface>,
+{
+    fn multi_method(&self) -> u32 {
+        unsafe {
+            let ptr = (self.ptr() as *mut u8)
+                .offset(<P::Target as Inherits<IMultiInterface>>::OFFSET)
+                as *mut IMultiInterface;
+            ((*(*ptr).vtbl).multi_method)(ptr)
+        }
+    }
+}
+
+impl Unknown for IMultiInterface {
+    unsafe fn query_interface(this: *mut Self, iid: &Guid) -> Option<*mut c_void> {
+        IUnknown::query_interface(this as *mut IUnknown, iid)
+    }
+
+    unsafe fn add_ref(this: *mut Self) -> usize {
+        IUnknown::add_ref(this as *mut IUnknown)
+    }
+
+    unsafe fn release(this: *mut Self) -> usize {
+        IUnknown::release(this as *mut IUnknown)
+    }
+}
+
+const POINTER_SIZE: isize = std::mem::size_of::<*mut ()>() as isize;
+
+unsafe impl Interface for IMultiInterface {
+    type Vtbl = IMultiInterfaceVtbl;
+
+    const IID: Guid = *b"dddddddddddddddd";
+
+    fn inherits(iid: &Guid) -> bool {
+        iid == &Self::IID || IMyInterface::inherits(iid) || IOtherInterface::inherits(iid)
+    }
+
+    fn base_offset(iid: &Guid) -> Option<isize> {
+        if iid == &Self::IID {
+            return Some(0);
+        }
+        if let Some(offset) = IMyInterface::base_offset(iid) {
+            return Some(offset);
+        }
+        if let Some(offset) = IOtherInterface::base_offset(iid) {
+            return Some(POINTER_SIZE + offset);
+        }
+        None
+    }
+}
+
+unsafe impl Inherits<IMyInterface> for IMultiInterface {}
+unsafe impl Inherits<IUnknown> for IMultiInterface {}
+unsafe impl Inherits<IOtherInterface> for IMultiInterface {
+    const OFFSET: isize = POINTER_SIZE;
+}
+
+impl IMultiInterface {
+    pub const fn make_vtbl<C, W, O, const ADJUST: isize>() -> IMultiInterfaceVtbl
+    where
+        C: IMultiInterfaceTrait + Class,
+        W: Wrapper<C>,
+        O: Offset,
+    {
+        unsafe extern "system" fn multi_method<C, W, O, const ADJUST: isize>(
+            this: *mut IMultiInterface,
+        ) -> u32
+        where
+            C: IMultiInterfaceTrait + Class,
+            W: Wrapper<C>,
+            O: Offset,
+        {
+            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;
+            let ptr = W::data_from_header(header_ptr);
+            (*ptr).multi_method()
+        }
+
+        IMultiInterfaceVtbl {
+            base: IMyInterface::make_vtbl::<C, W, O, ADJUST>(),
+            multi_method: multi_method::<C, W, O, ADJUST>,
+        }
+    }
+}
+
+unsafe impl<C, W, O> Construct<C, W, O> for IMultiInterface
+where
+    C: IMultiInterfaceTrait + Class,
+    W: Wrapper<C>,
+    O: Offset,
+{
+    const OBJ: IMultiInterface = IMultiInterface {
+        vtbl: &Self::make_vtbl::<C, W, O, 0>(),
+        __base1: IOtherInterface {
+            vtbl: &IOtherInterface::make_vtbl::<C, W, O, POINTER_SIZE>(),
+        },
+    };
+}
+
+struct MyClass3 {
+    x: u32,
+    y: u32,
+    z: u32,
+}
+
+impl Class for MyClass3 {
+    type Interfaces = (IUnknown, IMultiInterface);
+}
+
+impl IMyInterfaceTrait for MyClass3 {
+    fn my_method(&self) -> u32 {
+        self.x
+    }
+}
+
+impl IOtherInterfaceTrait for MyClass3 {
+    fn other_method(&self) -> u32 {
+        self.y
+    }
+}
+
+impl IMultiInterfaceTrait for MyClass3 {
+    fn multi_method(&self) -> u32 {
+        self.z
+    }
+}
+
+#[test]
+fn multiple_inheritance() {
+    let obj = ComWrapper::new(MyClass3 { x: 1, y: 2, z: 3 });
+
+    let multi = obj.to_com_ptr::<IMultiInterface>().unwrap();
+    assert_eq!(multi.my_method(), 1);
+    assert_eq!(multi.other_method(), 2);
+    assert_eq!(multi.multi_method(), 3);
+
+    let my_interface = obj.as_com_ref::<IMyInterface>().unwrap();
+    assert_eq!(my_interface.my_method(), 1);
+
+    let other_interface = obj.as_com_ref::<IOtherInterface>().unwrap();
+    assert_eq!(other_interface.other_method(), 2);
+    assert_eq!(
+        other_interface.as_ptr() as isize - multi.as_ptr() as isize,
+        POINTER_SIZE
+    );
+
+    let upcast = multi.as_com_ref().upcast::<IOtherInterface>();
+    assert_eq!(upcast.as_ptr(), other_interface.as_ptr());
+    assert_eq!(upcast.other_method(), 2);
+
+    let cast = upcast.cast::<IMultiInterface>().unwrap();
+    assert_eq!(cast.as_ptr(), multi.as_ptr());
+    assert_eq!(cast.multi_method(), 3);
+}
+
+struct MyClass4 {
+    x: u32,
+    y: u32,
+    z: u32,
+}
+
+impl Class for MyClass4 {
+    type Interfaces = (IMultiInterface, IOtherInterface, IMyInterface);
+}
+
+impl IMyInterfaceTrait for MyClass4 {
+    fn my_method(&self) -> u32 {
+        self.x
+    }
+}
+
+impl IOtherInterfaceTrait for MyClass4 {
+    fn other_method(&self) -> u32 {
+        self.y
+    }
+}
+
+impl IMultiInterfaceTrait for MyClass4 {
+    fn multi_method(&self) -> u32 {
+        self.z
+    }
+}
+
+#[test]
+fn multiple_inheritance_header_layout() {
+    let obj = ComWrapper::new(MyClass4 { x: 1, y: 2, z: 3 });
+
+    // IMultiInterface occupies two pointers at the start of the header
+    let multi = obj.to_com_ptr::<IMultiInterface>().unwrap();
+    let other = obj.as_com_ref::<IOtherInterface>().unwrap();
+    assert_eq!(
+        other.as_ptr() as isize - multi.as_ptr() as isize,
+        POINTER_SIZE
+    );
+    assert_eq!(multi.multi_method(), 3);
+    assert_eq!(other.other_method(), 2);
+
+    let other_header = <HeaderOffset<<MyClass4 as Class>::Interfaces, 1> as Offset>::OFFSET;
+    let my_header = <HeaderOffset<<MyClass4 as Class>::Interfaces, 2> as Offset>::OFFSET;
+    assert_eq!(other_header, 2 * POINTER_SIZE);
+    assert_eq!(my_header, 3 * POINTER_SIZE);
+
+    // The interfaces following IMultiInterface are located at their actual offsets
+    let header = multi.as_ptr() as *mut u8;
+    unsafe {
+        let other_obj = header.offset(other_header) as *mut IOtherInterface;
+        assert_eq!(((*(*other_obj).vtbl).other_method)(other_obj), 2);
+        let my_obj = header.offset(my_header) as *mut IMyInterface;
+        assert_eq!(((*(*my_obj).vtbl).my_method)(my_obj), 1);
+    }
+}
+
+#[test]
+fn bitfield_unit() {
+    let mut unit = BitfieldUnit::<3>::default();
+
+    unit.set(0, 3, 0b101);
+    unit.set(3, 10, 0x3ff);
+    unit.set(13, 4, 0b0110);
+    assert_eq!(unit.get(0, 3), 0b101);
+    assert_eq!(unit.get(3, 10), 0x3ff);
+    assert_eq!(unit.get(13, 4), 0b0110);
+
+    unit.set(3, 10, 0x155);
+    assert_eq!(unit.get(0, 3), 0b101);
+    assert_eq!(unit.get(3, 10), 0x155);
+    assert_eq!(unit.get(13, 4), 0b0110);
+
+    // Excess high bits are discarded.
+    unit.set(0, 3, 0b11010);
+    assert_eq!(unit.get(0, 3), 0b010);
+    assert_eq!(unit.get(3, 10), 0x155);
+
+    unit.set(17, 7, (-5i64) as u64);
+    assert_eq!(unit.get_signed(17, 7), -5);
+    assert_eq!(unit.get(17, 7), 0b1111011);
+    unit.set(17, 7, 5);
+    assert_eq!(unit.get_signed(17, 7), 5);
+}
+
+#[cfg(target_endian = "little")]
+#[test]
+fn bitfield_unit_bit_order() {
+    let mut unit = BitfieldUnit::<2>::default();
+
+    unit.set(4, 8, 0xab);
+    assert_eq!(unit.as_bytes(), &[0xb0, 0x0a]);
+    assert!(unit.get_bit(4));
+    assert!(!unit.get_bit(6));
+}
@@ -0,0 +1,236 @@
+use std::io::Write;
+
+use crate::error::Error;
+use crate::ir::Namespace;
+use crate::print::RustPrinter;
+use crate::targets::TargetNamespace;
+use crate::Generator;
+
+/// An output format for generated bindings.
+///
+/// A backend receives the definitions parsed by a [`Generator`] and writes output for them to a
+/// sink. The default backend, [`RustBackend`], generates Rust bindings; custom backends can be
+/// registered via [`Generator::backend`] to emit other kinds of output (e.g. C headers or
+/// interface manifests) while reusing the parsing and configuration provided by `Generator`.
+pub trait Backend {
+    /// Writes output for the global namespace `namespace` to `sink`.
+    ///
+    /// `generator` is the [`Generator`] which the backend was invoked from, whose code generation
+    /// options (such as renaming callbacks and IID expressions) are available through its
+    /// accessor methods, e.g. [`Generator::rename_type`]. Backends can report failures which don't correspond to another
+    /// [`Error`] variant using [`Error::Backend`].
+    fn emit(
+        &self,
+        generator: &Generator,
+        namespace: &Namespace,
+        sink: &mut dyn Write,
+    ) -> Result<(), Error>;
+
+    /// Writes output covering several targets to `sink`, given the global namespace parsed for
+    /// each `rustc` target triple in `namespaces`. Used instead of [`emit`](Self::emit) when
+    /// targets have been added via [`Generator::cfg_target`].
+    ///
+    /// The default implementation fails with [`Error::Backend`].
+    fn emit_targets(
+        &self,
+        generator: &Generator,
+        namespaces: &[(String, Namespace)],
+        sink: &mut dyn Write,
+    ) -> Result<(), Error> {
+        let _ = (generator, namespaces, sink);
+        Err(Error::Backend(
+            "backend does not support generating output for multiple targets".into(),
+        ))
+    }
+}
+
+/// The default [`Backend`], which generates Rust bindings.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct RustBackend;
+
+impl Backend for RustBackend {
+    fn emit(
+        &self,
+        generator: &Generator,
+        namespace: &Namespace,
+        sink: &mut dyn Write,
+    ) -> Result<(), Error> {
+        // Report every interface without an IID at once, rather than failing on the first one
+        if generator.iid_generator.is_none() {
+            let mut missing = Vec::new();
+            collect_missing_iids(namespace, "", &mut missing);
+            if !missing.is_empty() {
+                return Err(Error::MissingIids(missing));
+            }
+        }
+
+        let mut printer = RustPrinter::new(sink, generator);
+        printer.print_namespace(namespace)
+    }
+
+    fn emit_targets(
+        &self,
+        generator: &Generator,
+        namespaces: &[(String, Namespace)],
+        sink: &mut dyn Write,
+    ) -> Result<(), Error> {
+        if generator.iid_generator.is_none() {
+            let mut missing = Vec::new();
+            for (_, namespace) in namespaces {
+                let mut target_missing = Vec::new();
+                collect_missing_iids(namespace, "", &mut target_missing);
+                for path in target_missing {
+                    if !missing.contains(&path) {
+                        missing.push(path);
+                    }
+                }
+            }
+            if !missing.is_empty() {
+                return Err(Error::MissingIids(missing));
+            }
+        }
+
+        let targets = namespaces
+            .iter()
+            .map(|(target, _)| target.as_str())
+            .collect::<Vec<_>>();
+        let split =
+            TargetNamespace::split(&namespaces.iter().map(|(_, ns)| ns).collect::<Vec<_>>());
+
+        let mut printer = RustPrinter::new(sink, generator);
+        printer.print_target_namespace(&split, &targets)
+    }
+}
+
+fn collect_missing_iids(namespace: &Namespace, prefix: &str, missing: &mut Vec<String>) {
+    for record in &namespace.records {
+        let path = format!("{prefix}{}", record.name);
+        if record.is_interface() && record.iid.is_none() {
+            missing.push(path.clone());
+        }
+        collect_missing_iids(&record.inner, &format!("{path}::"), missing);
+    }
+
+    for typedef in &namespace.typedefs {
+        collect_missing_iids(
+            &typedef.inner,
+            &format!("{prefix}{}::", typedef.name),
+            missing,
+        );
+    }
+
+    for (name, child) in &namespace.children {
+        collect_missing_iids(child, &format!("{prefix}{name}::"), missing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Constant, Record, Type, Value};
+    use crate::test_util::{interface, method};
+
+    struct ConstantNames;
+
+    impl Backend for ConstantNames {
+        fn emit(
+            &self,
+            generator: &Generator,
+            namespace: &Namespace,
+            sink: &mut dyn Write,
+        ) -> Result<(), Error> {
+            for constant in &namespace.constants {
+                let name = generator.rename_constant(&constant.name);
+                writeln!(sink, "{}", name.as_ref().unwrap_or(&constant.name))?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_backend() {
+        let mut namespace = Namespace::new();
+        namespace.constants.push(Constant {
+            name: String::from("kValue"),
+            doc: None,
+            type_: Type::Int,
+            value: Value::Signed(1),
+        });
+        namespace.constants.push(Constant {
+            name: String::from("kOther"),
+            doc: None,
+            type_: Type::Int,
+            value: Value::Signed(2),
+        });
+
+        let mut output = Vec::new();
+        Generator::default()
+            .backend(ConstantNames)
+            .constant_renamer(|name| (name == "kOther").then(|| String::from("OTHER")))
+            .generate_from_ir(&namespace, &mut output)
+            .unwrap();
+
+        assert_eq!(output, b"kValue\nOTHER\n");
+    }
+
+    #[test]
+    fn missing_iids() {
+        let interface = |name: &str, iid| Record {
+            iid,
+            ..interface(name, vec![method("method")])
+        };
+
+        let mut namespace = Namespace::new();
+        namespace.records.push(interface("IFoo", None));
+        namespace.records.push(interface("IBar", Some([0; 16])));
+        namespace.records.push(interface("IBaz", None));
+
+        let mut root = Namespace::new();
+        root.children.insert(String::from("ns"), namespace);
+
+        let result = Generator::default().generate_from_ir(&root, Vec::new());
+        match result {
+            Err(Error::MissingIids(interfaces)) => assert_eq!(interfaces, ["ns::IFoo", "ns::IBaz"]),
+            _ => panic!("expected Error::MissingIids"),
+        }
+    }
+
+    #[test]
+    fn multiple_targets() {
+        let constant = |name: &str, value| Constant {
+            name: String::from(name),
+            doc: None,
+            type_: Type::Int,
+            value: Value::Signed(value),
+        };
+
+        let mut windows = Namespace::new();
+        windows.constants.push(constant("kShared", 0));
+        windows.constants.push(constant("kPlatform", 1));
+        let mut linux = Namespace::new();
+        linux.constants.push(constant("kShared", 0));
+        linux.constants.push(constant("kPlatform", 2));
+
+        let namespaces = [
+            (String::from("x86_64-pc-windows-msvc"), windows),
+            (String::from("x86_64-unknown-linux-gnu"), linux),
+        ];
+
+        let mut output = Vec::new();
+        RustBackend
+            .emit_targets(&Generator::default(), &namespaces, &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("compile_error!"));
+        assert!(output.contains("\npub const kShared: ::std::ffi::c_int = 0;\n"));
+        assert!(output.contains("#[cfg(all(target_arch = \"x86_64\", target_os = \"windows\", target_env = \"msvc\"))]\nmod __cfg_0 {"));
+        assert!(output.contains("    pub const kPlatform: ::std::ffi::c_int = 1;\n"));
+        assert!(output.contains("#[cfg(all(target_arch = \"x86_64\", target_os = \"linux\", target_env = \"gnu\"))]\npub use __cfg_1::*;"));
+
+        let result =
+            ConstantNames.emit_targets(&Generator::default(), &namespaces, &mut Vec::new());
+        assert!(matches!(result, Err(Error::Backend(_))));
+    }
+}
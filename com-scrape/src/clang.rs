@@ -1,5 +1,4 @@
 use std::any::Any;
-use std::error::Error;
 use std::ffi::{c_char, c_int, c_longlong, c_uint, c_ulong, c_ulonglong, c_void, CStr, CString};
 use std::fmt::Display;
 use std::marker::PhantomData;
@@ -12,6 +11,9 @@ use std::{fmt, ptr, slice};
 
 use clang_sys::*;
 
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::error::Error;
+
 macro_rules! c_str {
     ($str:literal) => {
         concat!($str, "\0").as_ptr() as *const c_char
@@ -28,15 +30,28 @@ impl TranslationUnit {
         source: &str,
         include_paths: &[PathBuf],
         target: Option<&str>,
-    ) -> Result<TranslationUnit, Box<dyn Error>> {
+        extra_args: &[String],
+        detailed_preprocessing_record: bool,
+    ) -> Result<TranslationUnit, Error> {
         let mut paths_cstrs = Vec::new();
         for include_path in include_paths {
-            paths_cstrs.push(CString::new(include_path.to_str().unwrap()).unwrap());
+            let path_str = include_path.to_str().ok_or_else(|| {
+                Error::InvalidString(format!(
+                    "include path {} is not valid UTF-8",
+                    include_path.display()
+                ))
+            })?;
+            paths_cstrs.push(CString::new(path_str)?);
         }
 
         let mut target_cstr = None;
         if let Some(target) = target {
-            target_cstr = Some(CString::new(target).unwrap());
+            target_cstr = Some(CString::new(target)?);
+        }
+
+        let mut extra_arg_cstrs = Vec::new();
+        for arg in extra_args {
+            extra_arg_cstrs.push(CString::new(arg.as_str())?);
         }
 
         unsafe {
@@ -49,6 +64,10 @@ impl TranslationUnit {
             if let Some(target) = &target_cstr {
                 args.extend_from_slice(&[c_str!("-target"), target.as_ptr()]);
             }
+            // Passed last so that they can override any of the arguments above
+            for arg in &extra_arg_cstrs {
+                args.push(arg.as_ptr());
+            }
 
             let filename = c_str!("header.h");
             let mut sources = [CXUnsavedFile {
@@ -57,6 +76,12 @@ impl TranslationUnit {
                 Length: source.len() as c_ulong,
             }];
 
+            let options = if detailed_preprocessing_record {
+                CXTranslationUnit_DetailedPreprocessingRecord
+            } else {
+                CXTranslationUnit_None
+            };
+
             let mut unit = MaybeUninit::uninit();
             let result = clang_parseTranslationUnit2(
                 index,
@@ -65,23 +90,63 @@ impl TranslationUnit {
                 args.len() as c_int,
                 sources.as_mut_ptr(),
                 sources.len() as u32,
-                CXTranslationUnit_None,
+                options,
                 unit.as_mut_ptr(),
             );
             let unit = unit.assume_init();
 
             if result != CXError_Success {
                 clang_disposeIndex(index);
-                return Err("error building translation unit".into());
+                return Err(Error::ParseFailed);
             }
 
             Ok(TranslationUnit { index, unit })
         }
     }
 
-    pub fn cursor(&self) -> Cursor {
+    pub fn cursor(&self) -> Cursor<'_> {
         unsafe { Cursor::from_raw(clang_getTranslationUnitCursor(self.unit)) }
     }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        unsafe {
+            let count = clang_getNumDiagnostics(self.unit);
+            for i in 0..count {
+                let diagnostic = clang_getDiagnostic(self.unit, i);
+
+                #[allow(non_upper_case_globals)]
+                let severity = match clang_getDiagnosticSeverity(diagnostic) {
+                    CXDiagnostic_Note => Severity::Note,
+                    CXDiagnostic_Warning => Severity::Warning,
+                    CXDiagnostic_Error => Severity::Error,
+                    CXDiagnostic_Fatal => Severity::Fatal,
+                    _ => Severity::Ignored,
+                };
+
+                let location = Location::from_raw(clang_getDiagnosticLocation(diagnostic));
+                let file_location = location.file_location();
+                let file = file_location
+                    .file_name()
+                    .map(|name| PathBuf::from(name.to_string_lossy().into_owned()));
+
+                let message = StringRef::from_raw(clang_getDiagnosticSpelling(diagnostic));
+
+                diagnostics.push(Diagnostic {
+                    severity,
+                    file,
+                    line: file_location.line(),
+                    column: file_location.column(),
+                    message: message.to_string_lossy().into_owned(),
+                });
+
+                clang_disposeDiagnostic(diagnostic);
+            }
+        }
+
+        diagnostics
+    }
 }
 
 impl Drop for TranslationUnit {
@@ -106,7 +171,12 @@ pub enum CursorKind {
     ClassDecl,
     FieldDecl,
     CxxMethod,
+    CxxConstructor,
+    CxxDestructor,
     CxxBaseSpecifier,
+    FunctionDecl,
+    LinkageSpec,
+    MacroDefinition,
     Other,
 }
 
@@ -137,7 +207,12 @@ impl<'a> Cursor<'a> {
             CXCursor_ClassDecl => CursorKind::ClassDecl,
             CXCursor_FieldDecl => CursorKind::FieldDecl,
             CXCursor_CXXMethod => CursorKind::CxxMethod,
+            CXCursor_Constructor => CursorKind::CxxConstructor,
+            CXCursor_Destructor => CursorKind::CxxDestructor,
             CXCursor_CXXBaseSpecifier => CursorKind::CxxBaseSpecifier,
+            CXCursor_FunctionDecl => CursorKind::FunctionDecl,
+            CXCursor_LinkageSpec => CursorKind::LinkageSpec,
+            CXCursor_MacroDefinition => CursorKind::MacroDefinition,
             _ => CursorKind::Other,
         }
     }
@@ -146,6 +221,12 @@ impl<'a> Cursor<'a> {
         unsafe { StringRef::from_raw(clang_getCursorSpelling(self.cursor)) }
     }
 
+    /// Returns the Unified Symbol Resolution string, which uniquely identifies the entity
+    /// referenced by this cursor across translation units.
+    pub fn usr(&self) -> StringRef<'a> {
+        unsafe { StringRef::from_raw(clang_getCursorUSR(self.cursor)) }
+    }
+
     pub fn is_anonymous(&self) -> bool {
         unsafe { clang_Cursor_isAnonymous(self.cursor) != 0 }
     }
@@ -154,6 +235,18 @@ impl<'a> Cursor<'a> {
         unsafe { Location::from_raw(clang_getCursorLocation(self.cursor)) }
     }
 
+    pub fn raw_comment(&self) -> Option<StringRef<'a>> {
+        unsafe {
+            let comment = clang_Cursor_getRawCommentText(self.cursor);
+            if clang_getCString(comment).is_null() {
+                clang_disposeString(comment);
+                return None;
+            }
+
+            Some(StringRef::from_raw(comment))
+        }
+    }
+
     pub fn is_in_system_header(&self) -> bool {
         unsafe {
             let location = clang_getCursorLocation(self.cursor);
@@ -161,10 +254,25 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    pub fn semantic_parent(&self) -> Option<Cursor<'a>> {
+        unsafe {
+            let parent = clang_getCursorSemanticParent(self.cursor);
+            if clang_Cursor_isNull(parent) != 0 {
+                return None;
+            }
+
+            Some(Cursor::from_raw(parent))
+        }
+    }
+
     pub fn is_definition(&self) -> bool {
         unsafe { clang_equalCursors(self.cursor, clang_getCursorDefinition(self.cursor)) != 0 }
     }
 
+    pub fn has_definition(&self) -> bool {
+        unsafe { clang_Cursor_isNull(clang_getCursorDefinition(self.cursor)) == 0 }
+    }
+
     pub fn type_(&self) -> Option<Type<'a>> {
         let type_ = unsafe { clang_getCursorType(self.cursor) };
         if type_.kind == CXType_Invalid {
@@ -239,10 +347,102 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    /// Returns the width of a bitfield in bits, or `None` if the cursor is not a bitfield.
+    pub fn bit_width(&self) -> Option<usize> {
+        unsafe {
+            if clang_Cursor_isBitField(self.cursor) == 0 {
+                return None;
+            }
+
+            let width = clang_getFieldDeclBitWidth(self.cursor);
+            if width < 0 {
+                None
+            } else {
+                Some(width as usize)
+            }
+        }
+    }
+
+    /// Returns the offset of a field in bits, if available.
+    pub fn offset_of_field(&self) -> Option<usize> {
+        let offset = unsafe { clang_Cursor_getOffsetOfField(self.cursor) };
+        if offset < 0 {
+            None
+        } else {
+            Some(offset as usize)
+        }
+    }
+
+    /// Returns the offset in bits of the base class specified by the `CXXBaseSpecifier` cursor
+    /// `base` within this record, if available. Requires `libclang` 20 or later.
+    pub fn offset_of_base(&self, base: &Cursor) -> Option<usize> {
+        if !clang_getOffsetOfBase::is_loaded() {
+            return None;
+        }
+
+        let offset = unsafe { clang_getOffsetOfBase(self.cursor, base.cursor) };
+        if offset < 0 {
+            None
+        } else {
+            Some(offset as usize)
+        }
+    }
+
+    pub fn is_static(&self) -> bool {
+        unsafe { clang_Cursor_getStorageClass(self.cursor) == CX_SC_Static }
+    }
+
+    pub fn is_function_inlined(&self) -> bool {
+        unsafe { clang_Cursor_isFunctionInlined(self.cursor) != 0 }
+    }
+
+    /// Returns the symbol name of a declaration, as mangled for the target.
+    pub fn mangling(&self) -> StringRef<'a> {
+        unsafe { StringRef::from_raw(clang_Cursor_getMangling(self.cursor)) }
+    }
+
+    pub fn is_macro_function_like(&self) -> bool {
+        unsafe { clang_Cursor_isMacroFunctionLike(self.cursor) != 0 }
+    }
+
     pub fn is_virtual(&self) -> bool {
         unsafe { clang_CXXMethod_isVirtual(self.cursor) != 0 }
     }
 
+    /// Returns `true` if a special member function is explicitly or implicitly defaulted.
+    /// Returns `true` if a method, constructor, or destructor is explicitly defaulted.
+    ///
+    /// If the loaded version of libclang doesn't provide `clang_CXXMethod_isDefaulted`, falls
+    /// back to checking whether the declaration ends with `= default`.
+    pub fn is_defaulted(&self) -> bool {
+        if clang_CXXMethod_isDefaulted::is_loaded() {
+            return unsafe { clang_CXXMethod_isDefaulted(self.cursor) != 0 };
+        }
+
+        let tokens = self.tokens();
+        let spelling = |index: Option<usize>| {
+            index
+                .and_then(|index| tokens.get(index))
+                .map(|token| token.spelling().to_bytes().to_vec())
+        };
+        let len = tokens.len();
+        spelling(len.checked_sub(2)).as_deref() == Some(b"=")
+            && spelling(len.checked_sub(1)).as_deref() == Some(b"default")
+    }
+
+    pub fn is_copy_constructor(&self) -> bool {
+        unsafe { clang_CXXConstructor_isCopyConstructor(self.cursor) != 0 }
+    }
+
+    pub fn is_move_constructor(&self) -> bool {
+        unsafe { clang_CXXConstructor_isMoveConstructor(self.cursor) != 0 }
+    }
+
+    /// Returns `true` if a `CXXBaseSpecifier` cursor specifies a virtual base class.
+    pub fn is_virtual_base(&self) -> bool {
+        unsafe { clang_isVirtualBase(self.cursor) != 0 }
+    }
+
     pub fn evaluate(&self) -> EvalResult<'a> {
         unsafe { EvalResult::from_raw(clang_Cursor_Evaluate(self.cursor)) }
     }
@@ -364,6 +564,20 @@ pub enum TypeKind {
     Typedef,
     ConstantArray,
     Elaborated,
+    FunctionProto,
+    FunctionNoProto,
+    Other,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum CallingConv {
+    C,
+    X86StdCall,
+    X86FastCall,
+    X86ThisCall,
+    X86VectorCall,
+    Win64,
+    X86_64SysV,
     Other,
 }
 
@@ -409,6 +623,8 @@ impl<'a> Type<'a> {
             CXType_Typedef => TypeKind::Typedef,
             CXType_ConstantArray => TypeKind::ConstantArray,
             CXType_Elaborated => TypeKind::Elaborated,
+            CXType_FunctionProto => TypeKind::FunctionProto,
+            CXType_FunctionNoProto => TypeKind::FunctionNoProto,
             _ => TypeKind::Other,
         }
     }
@@ -417,10 +633,39 @@ impl<'a> Type<'a> {
         unsafe { clang_isConstQualifiedType(self.type_) != 0 }
     }
 
+    /// Returns `true` if this is a plain old data type.
+    pub fn is_pod(&self) -> bool {
+        unsafe { clang_isPODType(self.type_) != 0 }
+    }
+
+    /// Returns the calling convention of a function type.
+    pub fn calling_conv(&self) -> CallingConv {
+        #[allow(non_upper_case_globals)]
+        match unsafe { clang_getFunctionTypeCallingConv(self.type_) } {
+            CXCallingConv_Default | CXCallingConv_C => CallingConv::C,
+            CXCallingConv_X86StdCall => CallingConv::X86StdCall,
+            CXCallingConv_X86FastCall => CallingConv::X86FastCall,
+            CXCallingConv_X86ThisCall => CallingConv::X86ThisCall,
+            CXCallingConv_X86VectorCall => CallingConv::X86VectorCall,
+            CXCallingConv_Win64 => CallingConv::Win64,
+            CXCallingConv_X86_64SysV => CallingConv::X86_64SysV,
+            _ => CallingConv::Other,
+        }
+    }
+
     pub fn size(&self) -> usize {
         unsafe { clang_Type_getSizeOf(self.type_) as usize }
     }
 
+    pub fn align(&self) -> Option<usize> {
+        let align = unsafe { clang_Type_getAlignOf(self.type_) };
+        if align < 0 {
+            None
+        } else {
+            Some(align as usize)
+        }
+    }
+
     #[allow(unused)]
     pub fn name(&self) -> StringRef<'a> {
         unsafe { StringRef::from_raw(clang_getTypeSpelling(self.type_)) }
@@ -470,6 +715,37 @@ impl<'a> Type<'a> {
         }
     }
 
+    pub fn result_type(&self) -> Option<Type<'a>> {
+        let result_type = unsafe { clang_getResultType(self.type_) };
+        if result_type.kind == CXType_Invalid {
+            None
+        } else {
+            Some(unsafe { Type::from_raw(result_type) })
+        }
+    }
+
+    pub fn num_arg_types(&self) -> Option<usize> {
+        let num_arg_types = unsafe { clang_getNumArgTypes(self.type_) };
+        if num_arg_types == -1 {
+            None
+        } else {
+            Some(num_arg_types as usize)
+        }
+    }
+
+    pub fn arg_type(&self, index: usize) -> Option<Type<'a>> {
+        let arg_type = unsafe { clang_getArgType(self.type_, index as c_uint) };
+        if arg_type.kind == CXType_Invalid {
+            None
+        } else {
+            Some(unsafe { Type::from_raw(arg_type) })
+        }
+    }
+
+    pub fn is_variadic(&self) -> bool {
+        unsafe { clang_isFunctionTypeVariadic(self.type_) != 0 }
+    }
+
     pub fn named_type(&self) -> Option<Type<'a>> {
         let named_type = unsafe { clang_Type_getNamedType(self.type_) };
         if named_type.kind == CXType_Invalid {
@@ -480,6 +756,7 @@ impl<'a> Type<'a> {
     }
 }
 
+#[derive(Copy, Clone)]
 pub struct Location<'a> {
     location: CXSourceLocation,
     _marker: PhantomData<&'a ()>,
@@ -526,7 +803,7 @@ impl<'a> Display for Location<'a> {
             write!(
                 f,
                 "{}:{}:{}",
-                filename.to_str().unwrap(),
+                filename.to_string_lossy(),
                 file_location.line(),
                 file_location.column()
             )?;
@@ -685,7 +962,7 @@ impl<'a> Tokens<'a> {
 
     pub fn get(&self, index: usize) -> Option<Token<'a>> {
         unsafe {
-            let slice = slice::from_raw_parts(self.ptr, self.len as usize);
+            let slice = slice::from_raw_parts(self.ptr, self.len);
             slice.get(index).map(|t| Token::from_raw(self.unit, *t))
         }
     }
@@ -694,7 +971,7 @@ impl<'a> Tokens<'a> {
 impl<'a> Drop for Tokens<'a> {
     fn drop(&mut self) {
         unsafe {
-            clang_disposeTokens(self.unit, self.ptr, self.len.try_into().unwrap());
+            clang_disposeTokens(self.unit, self.ptr, self.len as c_uint);
         }
     }
 }
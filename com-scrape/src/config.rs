@@ -0,0 +1,405 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostic::DiagnosticPolicy;
+use crate::error::Error;
+use crate::generator::Generator;
+use crate::iid::IidMacro;
+use crate::overload::OverloadNaming;
+
+/// Declarative configuration for a [`Generator`], loaded from a TOML or JSON file.
+///
+/// Each field corresponds to a [`Generator`] option; see the methods linked below for details.
+/// Keys are written in kebab-case, and all of them are optional:
+///
+/// ```toml
+/// headers = ["pluginterfaces/**/*.h"]
+/// skip-headers = ["pluginterfaces/test/*.h"]
+/// include-paths = ["."]
+/// std = "c++17"
+/// skip-types = ["FUID"]
+/// skip-interface-traits = ["FUnknown"]
+/// iid-macros = [{ name = "DECLARE_IID", style = "guid" }]
+/// query-interface-fn = "crate::query_interface"
+/// diagnostic-policy = "fail-on-error"
+/// macro-constants = true
+/// snake-case-methods = true
+///
+/// [defines]
+/// SMTG_OS_LINUX = "1"
+///
+/// [rename-types]
+/// FUnknown = "Unknown"
+///
+/// [rename-methods]
+/// "IComponent::getBusCount" = "bus_count"
+/// ```
+///
+/// Relative paths and glob patterns are resolved against a base directory, which for
+/// [`Generator::from_config`] is the directory containing the config file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Glob patterns for headers to generate bindings for. See [`Generator::header`].
+    pub headers: Vec<String>,
+    /// Glob patterns for headers to exclude from the files matched by `headers`.
+    pub skip_headers: Vec<String>,
+    /// See [`Generator::include_path`].
+    pub include_paths: Vec<PathBuf>,
+    /// See [`Generator::std`].
+    pub std: Option<String>,
+    /// Preprocessor macro definitions, by name. See [`Generator::define`].
+    pub defines: BTreeMap<String, String>,
+    /// See [`Generator::clang_arg`].
+    pub clang_args: Vec<String>,
+    /// See [`Generator::target`].
+    pub target: Option<String>,
+    /// See [`Generator::cfg_target`].
+    pub cfg_targets: Vec<String>,
+    /// See [`Generator::skip_type`].
+    pub skip_types: Vec<String>,
+    /// See [`Generator::skip_interface_trait`].
+    pub skip_interface_traits: Vec<String>,
+    /// See [`Generator::opaque_type`].
+    pub opaque_types: Vec<String>,
+    /// Rust types to use in place of C++ types, by qualified C++ name. See
+    /// [`Generator::map_type`].
+    pub map_types: BTreeMap<String, String>,
+    /// See [`Generator::allow_item`].
+    pub allow_items: Vec<String>,
+    /// See [`Generator::block_item`].
+    pub block_items: Vec<String>,
+    /// See [`Generator::allow_header`].
+    pub allow_headers: Vec<String>,
+    /// See [`Generator::block_header`].
+    pub block_headers: Vec<String>,
+    /// See [`Generator::iid_macro`].
+    pub iid_macros: Vec<IidMacro>,
+    /// A Rust expression evaluating to the `Guid` of an interface, in which `{name}` is replaced
+    /// with the interface name. See [`Generator::iid_generator`].
+    pub iid_expr: Option<String>,
+    /// See [`Generator::iid_constant_type`].
+    pub iid_constant_type: Option<String>,
+    /// See [`Generator::query_interface_fn`].
+    pub query_interface_fn: Option<String>,
+    /// See [`Generator::add_ref_fn`].
+    pub add_ref_fn: Option<String>,
+    /// See [`Generator::release_fn`].
+    pub release_fn: Option<String>,
+    /// New names for types, by C++ name. See [`Generator::type_renamer`].
+    pub rename_types: BTreeMap<String, String>,
+    /// New names for interface trait methods, keyed by `Interface::method`. See
+    /// [`Generator::method_renamer`].
+    pub rename_methods: BTreeMap<String, String>,
+    /// New names for fields, keyed by `Record::field`. See [`Generator::field_renamer`].
+    pub rename_fields: BTreeMap<String, String>,
+    /// New names for constants, by C++ name. See [`Generator::constant_renamer`].
+    pub rename_constants: BTreeMap<String, String>,
+    /// New names for modules, by default module name. See [`Generator::module_renamer`].
+    pub rename_modules: BTreeMap<String, String>,
+    /// See [`Generator::snake_case_methods`].
+    pub snake_case_methods: Option<bool>,
+    /// See [`Generator::overload_naming`].
+    pub overload_naming: Option<OverloadNaming>,
+    /// See [`Generator::diagnostic_policy`].
+    pub diagnostic_policy: Option<DiagnosticPolicy>,
+    /// See [`Generator::layout_tests`].
+    pub layout_tests: Option<bool>,
+    /// See [`Generator::fixed_width_integers`].
+    pub fixed_width_integers: Option<bool>,
+    /// See [`Generator::macro_constants`].
+    pub macro_constants: Option<bool>,
+    /// See [`Generator::allow_macro`].
+    pub allow_macros: Vec<String>,
+    /// See [`Generator::block_macro`].
+    pub block_macros: Vec<String>,
+}
+
+impl Config {
+    /// Parses a config from TOML.
+    pub fn from_toml(source: &str) -> Result<Config, Error> {
+        toml::from_str(source).map_err(|err| Error::Config(err.to_string()))
+    }
+
+    /// Parses a config from JSON.
+    pub fn from_json(source: &str) -> Result<Config, Error> {
+        serde_json::from_str(source).map_err(|err| Error::Config(err.to_string()))
+    }
+
+    /// Reads a config from `path`, which is parsed as JSON if it has a `.json` extension and as
+    /// TOML otherwise.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Config, Error> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+
+        let result = if path.extension().is_some_and(|ext| ext == "json") {
+            Config::from_json(&source)
+        } else {
+            Config::from_toml(&source)
+        };
+
+        result.map_err(|err| match err {
+            Error::Config(message) => Error::Config(format!("{}: {message}", path.display())),
+            err => err,
+        })
+    }
+
+    /// Expands the `headers` glob patterns relative to `base_dir`, excluding any paths matched by
+    /// `skip_headers`. The result is sorted so that it doesn't depend on directory iteration
+    /// order.
+    fn find_headers(&self, base_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        let glob_error = |err: glob::PatternError| Error::Config(err.to_string());
+
+        // Escape the base directory so that it is matched literally
+        let base_dir = PathBuf::from(glob::Pattern::escape(&base_dir.to_string_lossy()));
+
+        let mut skip = Vec::new();
+        for pattern in &self.skip_headers {
+            let pattern = base_dir.join(pattern);
+            skip.push(glob::Pattern::new(&pattern.to_string_lossy()).map_err(glob_error)?);
+        }
+
+        let mut headers = Vec::new();
+        for pattern in &self.headers {
+            let pattern = base_dir.join(pattern);
+            for path in glob::glob(&pattern.to_string_lossy()).map_err(glob_error)? {
+                let path = path.map_err(|err| Error::Io(err.into()))?;
+                if !skip.iter().any(|pattern| pattern.matches_path(&path)) {
+                    headers.push(path);
+                }
+            }
+        }
+
+        headers.sort();
+        headers.dedup();
+
+        Ok(headers)
+    }
+}
+
+impl Generator {
+    /// Creates a `Generator` from the config file at `path`. See [`Config`] for the format.
+    ///
+    /// Relative paths in the config are resolved against the directory containing the file.
+    pub fn from_config<P: AsRef<Path>>(path: P) -> Result<Generator, Error> {
+        let path = path.as_ref();
+        let config = Config::from_file(path)?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+
+        Generator::default().config(&config, base_dir)
+    }
+
+    /// Applies the options in `config`, resolving relative paths and glob patterns against
+    /// `base_dir`.
+    ///
+    /// List options are added to those already configured, and other options replace the
+    /// current value if they are present in `config`. Non-empty rename tables replace any
+    /// previously registered renaming callback of the same kind.
+    pub fn config<P: AsRef<Path>>(mut self, config: &Config, base_dir: P) -> Result<Self, Error> {
+        let base_dir = base_dir.as_ref();
+
+        for header in config.find_headers(base_dir)? {
+            self = self.header(header);
+        }
+        for path in &config.include_paths {
+            self = self.include_path(base_dir.join(path));
+        }
+        if let Some(std) = &config.std {
+            self = self.std(std);
+        }
+        for (name, value) in &config.defines {
+            self = self.define(name, value);
+        }
+        for arg in &config.clang_args {
+            self = self.clang_arg(arg);
+        }
+        if let Some(target) = &config.target {
+            self = self.target(target);
+        }
+        for target in &config.cfg_targets {
+            self = self.cfg_target(target);
+        }
+        for type_ in &config.skip_types {
+            self = self.skip_type(type_);
+        }
+        for interface in &config.skip_interface_traits {
+            self = self.skip_interface_trait(interface);
+        }
+        for type_ in &config.opaque_types {
+            self = self.opaque_type(type_);
+        }
+        for (type_, rust_type) in &config.map_types {
+            self = self.map_type(type_, rust_type);
+        }
+        for pattern in &config.allow_items {
+            self = self.allow_item(pattern);
+        }
+        for pattern in &config.block_items {
+            self = self.block_item(pattern);
+        }
+        for pattern in &config.allow_headers {
+            self = self.allow_header(pattern);
+        }
+        for pattern in &config.block_headers {
+            self = self.block_header(pattern);
+        }
+        for iid_macro in &config.iid_macros {
+            self = self.iid_macro(iid_macro.clone());
+        }
+        if let Some(expr) = &config.iid_expr {
+            let expr = expr.clone();
+            self = self.iid_generator(move |name| expr.replace("{name}", name));
+        }
+        if let Some(type_) = &config.iid_constant_type {
+            self = self.iid_constant_type(type_);
+        }
+        if let Some(f) = &config.query_interface_fn {
+            self = self.query_interface_fn(f);
+        }
+        if let Some(f) = &config.add_ref_fn {
+            self = self.add_ref_fn(f);
+        }
+        if let Some(f) = &config.release_fn {
+            self = self.release_fn(f);
+        }
+        if !config.rename_types.is_empty() {
+            let names = config.rename_types.clone();
+            self = self.type_renamer(move |name| names.get(name).cloned());
+        }
+        if !config.rename_methods.is_empty() {
+            let names = config.rename_methods.clone();
+            self = self.method_renamer(move |interface, method| {
+                names.get(&format!("{interface}::{method}")).cloned()
+            });
+        }
+        if !config.rename_fields.is_empty() {
+            let names = config.rename_fields.clone();
+            self = self.field_renamer(move |record, field| {
+                names.get(&format!("{record}::{field}")).cloned()
+            });
+        }
+        if !config.rename_constants.is_empty() {
+            let names = config.rename_constants.clone();
+            self = self.constant_renamer(move |name| names.get(name).cloned());
+        }
+        if !config.rename_modules.is_empty() {
+            let names = config.rename_modules.clone();
+            self = self.module_renamer(move |name| names.get(name).cloned());
+        }
+        if let Some(enabled) = config.snake_case_methods {
+            self = self.snake_case_methods(enabled);
+        }
+        if let Some(naming) = config.overload_naming {
+            self = self.overload_naming(naming);
+        }
+        if let Some(policy) = config.diagnostic_policy {
+            self = self.diagnostic_policy(policy);
+        }
+        if let Some(enabled) = config.layout_tests {
+            self = self.layout_tests(enabled);
+        }
+        if let Some(enabled) = config.fixed_width_integers {
+            self = self.fixed_width_integers(enabled);
+        }
+        if let Some(enabled) = config.macro_constants {
+            self = self.macro_constants(enabled);
+        }
+        for macro_ in &config.allow_macros {
+            self = self.allow_macro(macro_);
+        }
+        for macro_ in &config.block_macros {
+            self = self.block_macro(macro_);
+        }
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iid::IidStyle;
+
+    #[test]
+    fn toml_and_json() {
+        let toml = Config::from_toml(
+            r#"
+            headers = ["include/**/*.h"]
+            skip-types = ["FUID"]
+            diagnostic-policy = "fail-on-error"
+            macro-constants = true
+
+            [[iid-macros]]
+            name = "DECLARE_CLASS_IID"
+            style = "platform-words"
+            "#,
+        )
+        .unwrap();
+        let json = Config::from_json(
+            r#"{
+                "headers": ["include/**/*.h"],
+                "skip-types": ["FUID"],
+                "diagnostic-policy": "fail-on-error",
+                "macro-constants": true,
+                "iid-macros": [{"name": "DECLARE_CLASS_IID", "style": "platform-words"}]
+            }"#,
+        )
+        .unwrap();
+
+        for config in [toml, json] {
+            assert_eq!(config.headers, ["include/**/*.h"]);
+            assert_eq!(config.skip_types, ["FUID"]);
+            assert_eq!(
+                config.diagnostic_policy,
+                Some(DiagnosticPolicy::FailOnError)
+            );
+            assert_eq!(config.macro_constants, Some(true));
+            assert_eq!(config.layout_tests, None);
+            assert_eq!(config.iid_macros.len(), 1);
+            assert_eq!(config.iid_macros[0].style, IidStyle::PlatformWords);
+            assert_eq!(config.iid_macros[0].value_arg, 1);
+        }
+    }
+
+    #[test]
+    fn clang_args() {
+        let config = Config::from_toml(
+            r#"
+            std = "c++17"
+            clang-args = ["-fms-extensions"]
+
+            [defines]
+            DEVELOPMENT = "1"
+            SMTG_OS_LINUX = "1"
+            "#,
+        )
+        .unwrap();
+
+        let generator = Generator::default()
+            .clang_arg("-Wno-everything")
+            .config(&config, "")
+            .unwrap();
+
+        assert_eq!(generator.std.as_deref(), Some("c++17"));
+        assert_eq!(
+            generator.clang_args,
+            [
+                "-Wno-everything",
+                "-DDEVELOPMENT=1",
+                "-DSMTG_OS_LINUX=1",
+                "-fms-extensions"
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_key() {
+        assert!(matches!(
+            Config::from_toml("skip-type = [\"FUID\"]"),
+            Err(Error::Config(_))
+        ));
+    }
+}
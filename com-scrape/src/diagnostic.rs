@@ -0,0 +1,136 @@
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The severity of a [`Diagnostic`].
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Severity {
+    Ignored,
+    Note,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let name = match self {
+            Severity::Ignored => "ignored",
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Fatal => "fatal error",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// A diagnostic (error, warning, or note) emitted by `libclang` while parsing the input.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The file the diagnostic refers to, if any.
+    pub file: Option<PathBuf>,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        if let Some(file) = &self.file {
+            write!(f, "{}:{}:{}: ", file.display(), self.line, self.column)?;
+        }
+
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Controls which [`Diagnostic`]s cause [`Generator::generate`](crate::Generator::generate) to
+/// fail.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum DiagnosticPolicy {
+    /// Never fail because of diagnostics.
+    #[default]
+    Ignore,
+    /// Fail if `libclang` emits any errors.
+    FailOnError,
+    /// Fail if `libclang` emits any errors or warnings.
+    FailOnWarning,
+}
+
+impl DiagnosticPolicy {
+    pub(crate) fn threshold(&self) -> Option<Severity> {
+        match self {
+            DiagnosticPolicy::Ignore => None,
+            DiagnosticPolicy::FailOnError => Some(Severity::Error),
+            DiagnosticPolicy::FailOnWarning => Some(Severity::Warning),
+        }
+    }
+
+    /// Returns the diagnostics in `diagnostics` which are disallowed by this policy.
+    pub(crate) fn disallowed(&self, diagnostics: &[Diagnostic]) -> Vec<Diagnostic> {
+        let Some(threshold) = self.threshold() else {
+            return Vec::new();
+        };
+
+        diagnostics
+            .iter()
+            .filter(|d| d.severity >= threshold)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(severity: Severity) -> Diagnostic {
+        Diagnostic {
+            severity,
+            file: None,
+            line: 1,
+            column: 1,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn policy_threshold() {
+        let diagnostics = [
+            Severity::Ignored,
+            Severity::Note,
+            Severity::Warning,
+            Severity::Error,
+            Severity::Fatal,
+        ]
+        .map(diagnostic);
+
+        let severities = |policy: DiagnosticPolicy| {
+            policy
+                .disallowed(&diagnostics)
+                .iter()
+                .map(|d| d.severity)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(severities(DiagnosticPolicy::Ignore), []);
+        assert_eq!(
+            severities(DiagnosticPolicy::FailOnError),
+            [Severity::Error, Severity::Fatal]
+        );
+        assert_eq!(
+            severities(DiagnosticPolicy::FailOnWarning),
+            [Severity::Warning, Severity::Error, Severity::Fatal]
+        );
+    }
+}
@@ -0,0 +1,304 @@
+/// Converts the raw text of a C++ doc comment (including comment delimiters) into Markdown suitable
+/// for a rustdoc comment.
+///
+/// Common Doxygen commands are translated into their rustdoc equivalents (`\param` into a
+/// "Parameters" section, `\return` into a "Returns" section, `\code` into a fenced code block,
+/// etc.). Grouping commands which have no rustdoc equivalent are dropped. Returns `None` if the
+/// comment has no content.
+pub fn convert_comment(raw: &str) -> Option<String> {
+    let mut converter = Converter::new();
+
+    for line in raw.lines() {
+        converter.line(strip_delimiters(line));
+    }
+
+    converter.finish()
+}
+
+/// Removes comment delimiters (`/**`, `///<`, `*/`, leading `*`, etc.) from a single line of a
+/// comment, preserving any indentation which follows them.
+fn strip_delimiters(line: &str) -> &str {
+    let mut line = line.trim_end();
+
+    if let Some(rest) = line.strip_suffix("*/") {
+        line = rest.trim_end();
+    }
+
+    let trimmed = line.trim_start();
+    for prefix in [
+        "///<", "//!<", "/**<", "/*!<", "///", "//!", "/**", "/*!", "/*", "//",
+    ] {
+        if let Some(rest) = trimmed.strip_prefix(prefix) {
+            return rest;
+        }
+    }
+
+    if trimmed == "*" {
+        ""
+    } else if let Some(rest) = trimmed.strip_prefix("* ") {
+        rest
+    } else {
+        line
+    }
+}
+
+/// Splits a Doxygen command (e.g. `\param` or `@param`) off the start of a line, returning the
+/// command name and the remainder of the line.
+fn split_command(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix('\\').or_else(|| text.strip_prefix('@'))?;
+
+    let end = rest
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '{' && c != '}')
+        .unwrap_or(rest.len());
+    let (command, rest) = rest.split_at(end);
+    if command.is_empty() {
+        return None;
+    }
+
+    Some((command, rest.trim()))
+}
+
+struct Converter {
+    lines: Vec<String>,
+    code: Option<Vec<String>>,
+    in_parameters: bool,
+}
+
+impl Converter {
+    fn new() -> Converter {
+        Converter {
+            lines: Vec::new(),
+            code: None,
+            in_parameters: false,
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        let text = line.trim();
+
+        if let Some(code) = &mut self.code {
+            if let Some(("endcode", _)) = split_command(text) {
+                self.end_code();
+            } else {
+                code.push(line.to_string());
+            }
+            return;
+        }
+
+        if text.is_empty() {
+            self.blank();
+            self.in_parameters = false;
+            return;
+        }
+
+        let Some((command, rest)) = split_command(text) else {
+            self.lines.push(convert_inline(text));
+            return;
+        };
+
+        match command {
+            "code" | "code{" | "verbatim" => {
+                self.blank();
+                self.lines.push(String::from("```text"));
+                self.code = Some(Vec::new());
+            }
+            "param" | "tparam" => {
+                let rest = rest.strip_prefix('[').map_or(rest, |rest| {
+                    rest.split_once(']')
+                        .map_or(rest, |(_, rest)| rest.trim_start())
+                });
+                let (name, description) =
+                    rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let name = name.trim_end_matches([',', ':']);
+
+                if !self.in_parameters {
+                    self.section("Parameters");
+                    self.in_parameters = true;
+                }
+
+                let description = convert_inline(description);
+                if description.is_empty() {
+                    self.lines.push(format!("* `{name}`"));
+                } else {
+                    self.lines.push(format!("* `{name}`: {description}"));
+                }
+            }
+            "return" | "returns" | "result" | "retval" => {
+                self.section("Returns");
+                self.push(convert_inline(rest));
+            }
+            "see" | "sa" => {
+                self.blank();
+                let targets = rest
+                    .split([',', ' '])
+                    .filter(|target| !target.is_empty())
+                    .map(|target| format!("`{target}`"))
+                    .collect::<Vec<_>>();
+                self.lines.push(format!("See also: {}", targets.join(", ")));
+            }
+            "note" | "remark" | "remarks" | "attention" => self.callout("Note", rest),
+            "warning" => self.callout("Warning", rest),
+            "deprecated" => self.callout("Deprecated", rest),
+            "brief" | "short" | "details" => self.push(convert_inline(rest)),
+            "li" | "arg" => self.lines.push(format!("* {}", convert_inline(rest))),
+            "ingroup" | "defgroup" | "addtogroup" | "weakgroup" | "internal" | "endinternal"
+            | "{" | "}" | "file" | "class" | "struct" | "interface" | "fn" | "var" | "typedef"
+            | "enum" | "namespace" | "name" => {}
+            _ => self.lines.push(convert_inline(text)),
+        }
+    }
+
+    fn push(&mut self, line: String) {
+        if !line.is_empty() {
+            self.lines.push(line);
+        }
+    }
+
+    fn blank(&mut self) {
+        if matches!(self.lines.last(), Some(line) if !line.is_empty()) {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn section(&mut self, title: &str) {
+        self.in_parameters = false;
+        self.blank();
+        self.lines.push(format!("# {title}"));
+        self.lines.push(String::new());
+    }
+
+    fn callout(&mut self, title: &str, rest: &str) {
+        self.in_parameters = false;
+        self.blank();
+        self.lines.push(
+            format!("**{title}:** {}", convert_inline(rest))
+                .trim_end()
+                .to_string(),
+        );
+    }
+
+    fn end_code(&mut self) {
+        let Some(code) = self.code.take() else {
+            return;
+        };
+
+        let indent = code
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        for line in code {
+            self.lines
+                .push(line.get(indent..).unwrap_or("").trim_end().to_string());
+        }
+
+        self.lines.push(String::from("```"));
+        self.lines.push(String::new());
+    }
+
+    fn finish(mut self) -> Option<String> {
+        self.end_code();
+
+        while matches!(self.lines.last(), Some(line) if line.is_empty()) {
+            self.lines.pop();
+        }
+
+        let start = self.lines.iter().position(|line| !line.is_empty())?;
+
+        Some(self.lines[start..].join("\n"))
+    }
+}
+
+/// Converts inline Doxygen commands (`\c`, `\p`, `\a`, `\b`, `\ref`, etc.), which apply to the
+/// following word, into Markdown. Square brackets are escaped so that rustdoc does not attempt to
+/// resolve them as intra-doc links.
+fn convert_inline(text: &str) -> String {
+    let mut words = Vec::new();
+    let mut style = None;
+
+    let text = text.replace('[', "\\[").replace(']', "\\]");
+    for word in text.split_whitespace() {
+        if let Some(current) = style.take() {
+            let end = word.trim_end_matches(['.', ',', ';', ':', ')']).len();
+            let (word, punctuation) = word.split_at(end);
+            words.push(format!("{current}{word}{current}{punctuation}"));
+            continue;
+        }
+
+        match split_command(word) {
+            Some(("c" | "p" | "ref", "")) => style = Some("`"),
+            Some(("a" | "e" | "em", "")) => style = Some("*"),
+            Some(("b", "")) => style = Some("**"),
+            Some(("n", "")) => {}
+            _ => words.push(word.to_string()),
+        }
+    }
+
+    words.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::convert_comment;
+
+    #[test]
+    fn strips_delimiters() {
+        assert_eq!(convert_comment("/** Hello */").as_deref(), Some("Hello"));
+        assert_eq!(
+            convert_comment("///< Trailing").as_deref(),
+            Some("Trailing")
+        );
+        assert_eq!(
+            convert_comment("/**\n * First line.\n *\n * Second paragraph.\n */").as_deref(),
+            Some("First line.\n\nSecond paragraph.")
+        );
+        assert_eq!(convert_comment("/** */"), None);
+    }
+
+    #[test]
+    fn parameters_and_return() {
+        let raw = "/** Does a thing.\n\t\\param context, passed by the host\n\t\\param[out] result the result\n\t\\return kResultOk on success */";
+        assert_eq!(
+            convert_comment(raw).as_deref(),
+            Some(
+                "Does a thing.\n\n# Parameters\n\n* `context`: passed by the host\n* `result`: the result\n\n# Returns\n\nkResultOk on success"
+            )
+        );
+    }
+
+    #[test]
+    fn see_also_and_notes() {
+        let raw = "/** Text.\n\\see IComponent, IEditController\n@note Be careful. */";
+        assert_eq!(
+            convert_comment(raw).as_deref(),
+            Some("Text.\n\nSee also: `IComponent`, `IEditController`\n\n**Note:** Be careful.")
+        );
+    }
+
+    #[test]
+    fn groups_are_dropped() {
+        let raw = "/** Basic interface.\n\\ingroup pluginBase\n- [released: 3.0.0] */";
+        assert_eq!(
+            convert_comment(raw).as_deref(),
+            Some("Basic interface.\n- \\[released: 3.0.0\\]")
+        );
+    }
+
+    #[test]
+    fn code_blocks() {
+        let raw = "/** Example:\n\\code\n    if (x)\n        y ();\n\\endcode\nDone. */";
+        assert_eq!(
+            convert_comment(raw).as_deref(),
+            Some("Example:\n\n```text\nif (x)\n    y ();\n```\n\nDone.")
+        );
+    }
+
+    #[test]
+    fn inline_commands() {
+        assert_eq!(
+            convert_comment("/** Returns \\c true if \\a index is valid. */").as_deref(),
+            Some("Returns `true` if *index* is valid.")
+        );
+    }
+}
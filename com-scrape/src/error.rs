@@ -0,0 +1,108 @@
+use std::ffi::NulError;
+use std::fmt::{self, Display};
+use std::io;
+use std::str::Utf8Error;
+
+use crate::diagnostic::Diagnostic;
+
+/// Errors that can occur while generating bindings.
+#[derive(Debug)]
+pub enum Error {
+    /// `libclang` could not be loaded.
+    ClangLoad(String),
+    /// `libclang` was unable to build a translation unit from the input.
+    ParseFailed,
+    /// `libclang` emitted diagnostics which are disallowed by the configured
+    /// [`DiagnosticPolicy`](crate::DiagnosticPolicy). Contains the offending diagnostics.
+    Diagnostics(Vec<Diagnostic>),
+    /// A declaration uses a type which cannot be represented in the generated bindings.
+    UnsupportedType { location: String, kind: String },
+    /// A declaration relies on a C++ feature or ABI detail which cannot be represented in the
+    /// generated bindings for the target, such as an unsupported calling convention.
+    Unsupported { location: String, message: String },
+    /// A [`Generator`](crate::Generator) option required for generating bindings was not
+    /// provided.
+    MissingOption { name: &'static str },
+    /// No IID was found for the listed interfaces, and no fallback was provided via
+    /// [`Generator::iid_generator`](crate::Generator::iid_generator).
+    MissingIids(Vec<String>),
+    /// `libclang` returned incomplete or unexpected information for a declaration.
+    Malformed { location: String, message: String },
+    /// A [`Namespace`](crate::ir::Namespace) passed to
+    /// [`Generator::generate_from_ir`](crate::Generator::generate_from_ir) contains a definition
+    /// which the parser never produces, such as an integer type of an unsupported size.
+    InvalidIr(String),
+    /// An allowlist or blocklist pattern could not be parsed.
+    InvalidPattern { pattern: String, message: String },
+    /// A string was not valid UTF-8 or contained an interior null byte.
+    InvalidString(String),
+    /// A [`Config`](crate::Config) could not be parsed or applied.
+    Config(String),
+    /// An error occurred while reading input or writing the generated bindings.
+    Io(io::Error),
+    /// A custom [`Backend`](crate::Backend) failed to generate output.
+    Backend(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Error::ClangLoad(message) => write!(f, "unable to load libclang: {message}"),
+            Error::ParseFailed => write!(f, "error building translation unit"),
+            Error::Diagnostics(diagnostics) => {
+                write!(f, "libclang reported problems while parsing the input:")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{diagnostic}")?;
+                }
+                Ok(())
+            }
+            Error::UnsupportedType { location, kind } => {
+                write!(f, "error at {location}: unsupported type {kind}")
+            }
+            Error::Unsupported { location, message } => {
+                write!(f, "error at {location}: {message}")
+            }
+            Error::MissingOption { name } => write!(f, "no value provided for {name}"),
+            Error::MissingIids(interfaces) => {
+                write!(f, "no IID found for interfaces: {}", interfaces.join(", "))
+            }
+            Error::Malformed { location, message } => write!(f, "error at {location}: {message}"),
+            Error::InvalidIr(message) => write!(f, "invalid IR: {message}"),
+            Error::InvalidPattern { pattern, message } => {
+                write!(f, "invalid pattern `{pattern}`: {message}")
+            }
+            Error::InvalidString(message) => write!(f, "invalid string: {message}"),
+            Error::Config(message) => write!(f, "invalid config: {message}"),
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Backend(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Backend(err) => Some(&**err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(err: Utf8Error) -> Error {
+        Error::InvalidString(err.to_string())
+    }
+}
+
+impl From<NulError> for Error {
+    fn from(err: NulError) -> Error {
+        Error::InvalidString(err.to_string())
+    }
+}
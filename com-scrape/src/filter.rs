@@ -0,0 +1,333 @@
+use std::collections::{HashMap, HashSet};
+
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+
+use crate::error::Error;
+use crate::ir::Namespace;
+use crate::Generator;
+
+/// The compiled allowlist and blocklist rules of a [`Generator`].
+pub struct Filter {
+    allow_items: Vec<Regex>,
+    block_items: Vec<Regex>,
+    allow_headers: Vec<Pattern>,
+    block_headers: Vec<Pattern>,
+}
+
+fn compile_regexes(patterns: &[String]) -> Result<Vec<Regex>, Error> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            // Patterns must match the entire name
+            Regex::new(&format!("^(?:{pattern})$")).map_err(|err| Error::InvalidPattern {
+                pattern: pattern.clone(),
+                message: err.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn compile_globs(patterns: &[String]) -> Result<Vec<Pattern>, Error> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern).map_err(|err| Error::InvalidPattern {
+                pattern: pattern.clone(),
+                message: err.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn matches_header(patterns: &[Pattern], file: Option<&str>) -> bool {
+    let Some(file) = file else {
+        return false;
+    };
+
+    // Match the same paths regardless of the platform's path separator
+    let file = file.replace('\\', "/");
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+
+    patterns
+        .iter()
+        .any(|pattern| pattern.matches_with(&file, options))
+}
+
+impl Filter {
+    pub fn new(options: &Generator) -> Result<Filter, Error> {
+        Ok(Filter {
+            allow_items: compile_regexes(&options.allow_items)?,
+            block_items: compile_regexes(&options.block_items)?,
+            allow_headers: compile_globs(&options.allow_headers)?,
+            block_headers: compile_globs(&options.block_headers)?,
+        })
+    }
+
+    /// Returns `true` if any allowlist rules are configured, in which case only allowed items and
+    /// their dependencies are kept.
+    pub fn has_allowlist(&self) -> bool {
+        !self.allow_items.is_empty() || !self.allow_headers.is_empty()
+    }
+
+    /// Returns `true` if the item with qualified name `path`, declared in `file`, is blocked.
+    pub fn is_blocked(&self, path: &str, file: Option<&str>) -> bool {
+        self.block_items.iter().any(|regex| regex.is_match(path))
+            || matches_header(&self.block_headers, file)
+    }
+
+    /// Returns `true` if items declared in `file` are allowed.
+    pub fn is_header_allowed(&self, file: Option<&str>) -> bool {
+        matches_header(&self.allow_headers, file)
+    }
+
+    /// Removes all items from `namespace` except those which are allowed (by name, or by being
+    /// declared in an allowed header as recorded in `header_roots`) and the items they depend on,
+    /// transitively. `dependencies` maps the qualified name of each item to the qualified names
+    /// of the items it refers to.
+    pub fn retain_allowed(
+        &self,
+        namespace: &mut Namespace,
+        header_roots: &HashSet<String>,
+        dependencies: &HashMap<String, HashSet<String>>,
+    ) {
+        let mut paths = Vec::new();
+        collect_paths(namespace, "", &mut paths);
+
+        let mut pending = paths
+            .into_iter()
+            .filter(|path| {
+                header_roots.contains(path)
+                    || self.allow_items.iter().any(|regex| regex.is_match(path))
+            })
+            .collect::<Vec<_>>();
+
+        let mut kept = HashSet::new();
+        while let Some(path) = pending.pop() {
+            if kept.insert(path.clone()) {
+                pending.extend(dependencies.get(&path).into_iter().flatten().cloned());
+            }
+        }
+
+        retain_items(namespace, "", &kept);
+    }
+}
+
+/// Removes the opaque records in `namespace` which stand in for records that are declared but
+/// never defined, unless another item refers to them according to `dependencies`. Records
+/// configured as opaque via [`Generator::opaque_type`] are always kept.
+pub fn retain_referenced_opaque_records(
+    namespace: &mut Namespace,
+    dependencies: &HashMap<String, HashSet<String>>,
+    opaque_types: &HashSet<String>,
+) {
+    let referenced = dependencies
+        .values()
+        .flatten()
+        .map(String::as_str)
+        .collect::<HashSet<_>>();
+    retain_opaque_records(namespace, "", &|path, name| {
+        referenced.contains(path) || opaque_types.contains(name)
+    });
+}
+
+fn retain_opaque_records(
+    namespace: &mut Namespace,
+    prefix: &str,
+    keep: &dyn Fn(&str, &str) -> bool,
+) {
+    namespace
+        .opaque_records
+        .retain(|record| keep(&format!("{prefix}{}", record.name), &record.name));
+
+    for (name, child) in &mut namespace.children {
+        retain_opaque_records(child, &format!("{prefix}{name}::"), keep);
+    }
+}
+
+/// Collects the qualified names of the items declared directly in `namespace` and its children.
+fn collect_paths(namespace: &Namespace, prefix: &str, paths: &mut Vec<String>) {
+    let names = namespace
+        .typedefs
+        .iter()
+        .map(|typedef| &typedef.name)
+        .chain(namespace.records.iter().map(|record| &record.name))
+        .chain(namespace.opaque_records.iter().map(|record| &record.name))
+        .chain(namespace.functions.iter().map(|function| &function.name))
+        .chain(namespace.constants.iter().map(|constant| &constant.name));
+    paths.extend(names.map(|name| format!("{prefix}{name}")));
+
+    for (name, child) in &namespace.children {
+        collect_paths(child, &format!("{prefix}{name}::"), paths);
+    }
+}
+
+fn retain_items(namespace: &mut Namespace, prefix: &str, kept: &HashSet<String>) {
+    let is_kept = |name: &str| kept.contains(&format!("{prefix}{name}"));
+
+    namespace.typedefs.retain(|typedef| is_kept(&typedef.name));
+    namespace.records.retain(|record| is_kept(&record.name));
+    namespace
+        .opaque_records
+        .retain(|record| is_kept(&record.name));
+    namespace
+        .functions
+        .retain(|function| is_kept(&function.name));
+    namespace
+        .constants
+        .retain(|constant| is_kept(&constant.name));
+    // Constants produced by a constant parser can't be analyzed, so they are only kept when no
+    // allowlist is in effect.
+    namespace.unparsed_constants.clear();
+
+    for (name, child) in &mut namespace.children {
+        retain_items(child, &format!("{prefix}{name}::"), kept);
+    }
+    namespace.children.retain(|_, child| !child.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{OpaqueRecord, Type};
+    use crate::test_util::{record, typedef};
+
+    fn filter(allow_items: &[&str]) -> Filter {
+        let mut generator = Generator::default();
+        for item in allow_items {
+            generator = generator.allow_item(item);
+        }
+        Filter::new(&generator).unwrap()
+    }
+
+    #[test]
+    fn dependency_closure() {
+        let mut namespace = Namespace::new();
+        namespace.typedefs.push(typedef("int32", Type::Int));
+        namespace.typedefs.push(typedef("tresult", Type::Int));
+        namespace.typedefs.push(typedef("unused", Type::Int));
+        namespace.records.push(record("FUnknown"));
+        namespace.records.push(record("IFoo"));
+        namespace.records.push(record("Bar"));
+        namespace.records.push(record("IUnrelated"));
+
+        let mut root = Namespace::new();
+        root.children.insert(String::from("Steinberg"), namespace);
+        root.children.insert(String::from("Other"), {
+            let mut other = Namespace::new();
+            other.typedefs.push(typedef("int32", Type::Int));
+            other
+        });
+
+        let dependencies = [
+            ("Steinberg::tresult", &["Steinberg::int32"][..]),
+            (
+                "Steinberg::IFoo",
+                &[
+                    "Steinberg::FUnknown",
+                    "Steinberg::Bar",
+                    "Steinberg::tresult",
+                ],
+            ),
+            ("Steinberg::Bar", &["Steinberg::int32"]),
+            ("Steinberg::IUnrelated", &["Other::int32"]),
+        ]
+        .into_iter()
+        .map(|(path, dependencies)| {
+            let dependencies = dependencies.iter().map(|d| d.to_string()).collect();
+            (path.to_string(), dependencies)
+        })
+        .collect();
+
+        filter(&["Steinberg::IFoo"]).retain_allowed(&mut root, &HashSet::new(), &dependencies);
+
+        let namespace = &root.children["Steinberg"];
+        let typedefs = namespace
+            .typedefs
+            .iter()
+            .map(|t| &t.name)
+            .collect::<Vec<_>>();
+        let records = namespace
+            .records
+            .iter()
+            .map(|r| &r.name)
+            .collect::<Vec<_>>();
+        assert_eq!(typedefs, ["int32", "tresult"]);
+        assert_eq!(records, ["FUnknown", "IFoo", "Bar"]);
+
+        // Dependencies are resolved by qualified name, so same-named items elsewhere are removed.
+        assert!(!root.children.contains_key("Other"));
+    }
+
+    #[test]
+    fn unreferenced_opaque_records() {
+        let opaque = |name: &str| OpaqueRecord {
+            name: String::from(name),
+            doc: None,
+        };
+
+        let mut namespace = Namespace::new();
+        namespace.records.push(record("IFoo"));
+        namespace.opaque_records.push(opaque("Referenced"));
+        namespace.opaque_records.push(opaque("Unreferenced"));
+        namespace.opaque_records.push(opaque("Configured"));
+
+        let mut root = Namespace::new();
+        root.children.insert(String::from("Steinberg"), namespace);
+
+        let dependencies = HashMap::from([(
+            String::from("Steinberg::IFoo"),
+            HashSet::from([String::from("Steinberg::Referenced")]),
+        )]);
+        let opaque_types = HashSet::from([String::from("Configured")]);
+        retain_referenced_opaque_records(&mut root, &dependencies, &opaque_types);
+
+        let names = root.children["Steinberg"]
+            .opaque_records
+            .iter()
+            .map(|r| &r.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Referenced", "Configured"]);
+        assert_eq!(root.children["Steinberg"].records.len(), 1);
+    }
+
+    #[test]
+    fn patterns_match_whole_name() {
+        let mut namespace = Namespace::new();
+        namespace.typedefs.push(typedef("IFoo", Type::Int));
+        namespace.typedefs.push(typedef("IFooBar", Type::Int));
+
+        filter(&["IFoo"]).retain_allowed(&mut namespace, &HashSet::new(), &HashMap::new());
+
+        assert_eq!(namespace.typedefs.len(), 1);
+        assert_eq!(namespace.typedefs[0].name, "IFoo");
+    }
+
+    #[test]
+    fn headers() {
+        let generator = Generator::default()
+            .block_header("**/test/*.h")
+            .allow_header("**/vst/*.h");
+        let filter = Filter::new(&generator).unwrap();
+
+        assert!(filter.is_blocked("A", Some("sdk/pluginterfaces/test/itest.h")));
+        assert!(filter.is_blocked("A", Some("sdk\\pluginterfaces\\test\\itest.h")));
+        assert!(!filter.is_blocked("A", Some("sdk/pluginterfaces/test/sub/itest.h")));
+        assert!(!filter.is_blocked("A", None));
+
+        assert!(filter.is_header_allowed(Some("/sdk/pluginterfaces/vst/ivstaudioprocessor.h")));
+        assert!(!filter.is_header_allowed(Some("/sdk/pluginterfaces/base/funknown.h")));
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        let generator = Generator::default().block_item("(");
+        assert!(matches!(
+            Filter::new(&generator),
+            Err(Error::InvalidPattern { .. })
+        ));
+    }
+}
@@ -1,59 +1,151 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::error::Error;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
+use crate::backend::{Backend, RustBackend};
 use crate::clang::*;
-use crate::parse::*;
-use crate::print::*;
+use crate::diagnostic::{Diagnostic, DiagnosticPolicy};
+use crate::error::Error;
+use crate::iid::IidMacro;
+use crate::ir::Namespace;
+use crate::overload::OverloadNaming;
 
-const HOST_TARGET: &'static str = include_str!(concat!(env!("OUT_DIR"), "/host-target.txt"));
+const HOST_TARGET: &str = include_str!(concat!(env!("OUT_DIR"), "/host-target.txt"));
 
 // Some target triples are different between rustc and clang.
 // See https://github.com/rust-lang/rust-bindgen/blob/05ebcace15a8784e5a5b1001a3b755b866fac901/bindgen/lib.rs#L670
 fn rust_to_clang_target(rust_target: &str) -> String {
-    if rust_target.starts_with("aarch64-apple-") {
-        return "arm64-apple-".to_owned() + &rust_target["aarch64-apple-".len()..];
+    if let Some(rest) = rust_target.strip_prefix("aarch64-apple-") {
+        return "arm64-apple-".to_owned() + rest;
     }
 
     rust_target.to_owned()
 }
 
+type ConstantParser = Box<dyn Fn(&[String]) -> Option<String>>;
+type IidGenerator = Box<dyn Fn(&str) -> String>;
+type Renamer = Box<dyn Fn(&str) -> Option<String>>;
+type MemberRenamer = Box<dyn Fn(&str, &str) -> Option<String>>;
+
 /// Builder struct for configuring and generating bindings.
+#[derive(Default)]
 pub struct Generator {
+    pub(crate) target: Option<String>,
+    pub(crate) cfg_targets: Vec<String>,
+    pub(crate) headers: Vec<PathBuf>,
     pub(crate) include_paths: Vec<PathBuf>,
+    pub(crate) std: Option<String>,
+    pub(crate) clang_args: Vec<String>,
     pub(crate) skip_types: HashSet<String>,
     pub(crate) skip_interface_traits: HashSet<String>,
-    pub(crate) constant_parser: Option<Box<dyn Fn(&[String]) -> Option<String>>>,
-    pub(crate) iid_generator: Option<Box<dyn Fn(&str) -> String>>,
+    pub(crate) opaque_types: HashSet<String>,
+    pub(crate) type_map: HashMap<String, String>,
+    pub(crate) allow_items: Vec<String>,
+    pub(crate) block_items: Vec<String>,
+    pub(crate) allow_headers: Vec<String>,
+    pub(crate) block_headers: Vec<String>,
+    pub(crate) constant_parser: Option<ConstantParser>,
+    pub(crate) iid_generator: Option<IidGenerator>,
+    pub(crate) iid_macros: Vec<IidMacro>,
+    pub(crate) iid_constant_type: Option<String>,
     pub(crate) query_interface_fn: Option<String>,
     pub(crate) add_ref_fn: Option<String>,
     pub(crate) release_fn: Option<String>,
+    pub(crate) type_renamer: Option<Renamer>,
+    pub(crate) method_renamer: Option<MemberRenamer>,
+    pub(crate) field_renamer: Option<MemberRenamer>,
+    pub(crate) constant_renamer: Option<Renamer>,
+    pub(crate) module_renamer: Option<Renamer>,
+    pub(crate) snake_case_methods: bool,
+    pub(crate) overload_naming: OverloadNaming,
+    pub(crate) diagnostic_policy: DiagnosticPolicy,
+    pub(crate) layout_tests: bool,
+    pub(crate) fixed_width_integers: bool,
+    pub(crate) macro_constants: bool,
+    pub(crate) allow_macros: HashSet<String>,
+    pub(crate) block_macros: HashSet<String>,
+    pub(crate) backend: Option<Box<dyn Backend>>,
 }
 
-impl Default for Generator {
-    fn default() -> Generator {
-        Generator {
-            include_paths: Vec::new(),
-            skip_types: HashSet::new(),
-            skip_interface_traits: HashSet::new(),
-            constant_parser: None,
-            iid_generator: None,
-            query_interface_fn: None,
-            add_ref_fn: None,
-            release_fn: None,
-        }
+impl Generator {
+    /// Sets the target (as a `rustc` target triple) for which bindings will be generated.
+    ///
+    /// Defaults to the value of the `TARGET` environment variable if it is set (as it is for build
+    /// scripts), and to the host target otherwise.
+    pub fn target<T: AsRef<str>>(mut self, target: T) -> Self {
+        self.target = Some(target.as_ref().to_string());
+        self
+    }
+
+    /// Adds `target` (as a `rustc` target triple) to the targets for which bindings are generated
+    /// in a single pass.
+    ///
+    /// If any such targets are configured, [`generate`](Self::generate) parses the input once for
+    /// each of them instead of for [`target`](Self::target), and outputs one set of bindings which
+    /// works on all of them. Definitions which are the same for every target are generated
+    /// unconditionally, while those which differ (e.g. due to struct packing or vtable layout) are
+    /// generated once per variant, gated by `#[cfg(...)]` on the targets sharing that variant.
+    /// Compiling the bindings for any other target results in an error.
+    pub fn cfg_target<T: AsRef<str>>(mut self, target: T) -> Self {
+        self.cfg_targets.push(target.as_ref().to_string());
+        self
+    }
+
+    /// Adds each of `targets` as with [`cfg_target`](Self::cfg_target).
+    pub fn cfg_targets<'a, T: AsRef<[&'a str]>>(mut self, targets: T) -> Self {
+        self.cfg_targets
+            .extend(targets.as_ref().iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Adds the header at `path` to the input.
+    ///
+    /// Headers are included in the order they are added, before the `source` passed to
+    /// [`generate`](Self::generate) or [`parse`](Self::parse), which can then be empty.
+    pub fn header<T: AsRef<Path>>(mut self, path: T) -> Self {
+        self.headers.push(path.as_ref().to_path_buf());
+        self
     }
-}
 
-impl Generator {
     /// Adds `path` to the list of include paths to pass to `libclang`.
     pub fn include_path<T: AsRef<Path>>(mut self, path: T) -> Self {
         self.include_paths.push(path.as_ref().to_path_buf());
         self
     }
 
+    /// Sets the C++ language standard (e.g. `c++17`) used for parsing, passed to `libclang` as
+    /// `-std`.
+    ///
+    /// Defaults to the default standard of the `libclang` in use.
+    pub fn std<T: AsRef<str>>(mut self, std: T) -> Self {
+        self.std = Some(std.as_ref().to_string());
+        self
+    }
+
+    /// Defines the preprocessor macro `name` as `value`, as with `#define name value`.
+    ///
+    /// This can be used to configure headers which depend on platform or feature macros.
+    pub fn define<T: AsRef<str>, U: AsRef<str>>(self, name: T, value: U) -> Self {
+        self.clang_arg(format!("-D{}={}", name.as_ref(), value.as_ref()))
+    }
+
+    /// Adds `arg` to the command-line arguments passed to `libclang` (e.g. `-fms-extensions`).
+    ///
+    /// Arguments are passed in the order they are added (along with those added by
+    /// [`define`](Self::define)), after the arguments for the other options.
+    pub fn clang_arg<T: AsRef<str>>(mut self, arg: T) -> Self {
+        self.clang_args.push(arg.as_ref().to_string());
+        self
+    }
+
+    /// Adds each of `args` as with [`clang_arg`](Self::clang_arg).
+    pub fn clang_args<'a, T: AsRef<[&'a str]>>(mut self, args: T) -> Self {
+        self.clang_args
+            .extend(args.as_ref().iter().map(|s| s.to_string()));
+        self
+    }
+
     /// Do not generate bindings for `type_`.
     pub fn skip_type<T: AsRef<str>>(mut self, type_: T) -> Self {
         self.skip_types.insert(type_.as_ref().to_string());
@@ -81,6 +173,78 @@ impl Generator {
         self
     }
 
+    /// Generate an opaque type for `type_`, even if its definition is available.
+    ///
+    /// Opaque types are zero-sized, cannot be constructed, and can only be used behind pointers.
+    /// Records which are declared but never defined are generated as opaque types when another
+    /// item refers to them.
+    pub fn opaque_type<T: AsRef<str>>(mut self, type_: T) -> Self {
+        self.opaque_types.insert(type_.as_ref().to_string());
+        self
+    }
+
+    /// Generate opaque types for `types`, even if their definitions are available.
+    pub fn opaque_types<'a, T: AsRef<[&'a str]>>(mut self, types: T) -> Self {
+        self.opaque_types
+            .extend(types.as_ref().iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Use the Rust type `rust_type` wherever the C++ typedef, enum, or record `type_` is used as
+    /// the type of a field, argument, or return value.
+    ///
+    /// `type_` is the qualified C++ name (e.g. `Steinberg::TUID`), and `rust_type` should be a
+    /// path which resolves anywhere in the bindings (e.g. `crate::Tuid`). The Rust type must have
+    /// the same layout as the C++ type; when [`layout_tests`](Self::layout_tests) are enabled,
+    /// assertions are generated which check that their sizes and alignments match.
+    pub fn map_type<T: AsRef<str>, U: AsRef<str>>(mut self, type_: T, rust_type: U) -> Self {
+        self.type_map
+            .insert(type_.as_ref().to_string(), rust_type.as_ref().to_string());
+        self
+    }
+
+    /// Only generate bindings for items whose qualified name (e.g. `Steinberg::Vst::IComponent`)
+    /// matches the regular expression `pattern`, along with the items they depend on.
+    ///
+    /// The pattern must match the entire name. If no items or headers are explicitly allowed,
+    /// bindings are generated for all items which aren't blocked.
+    pub fn allow_item<T: AsRef<str>>(mut self, pattern: T) -> Self {
+        self.allow_items.push(pattern.as_ref().to_string());
+        self
+    }
+
+    /// Do not generate bindings for items whose qualified name matches the regular expression
+    /// `pattern`.
+    ///
+    /// The pattern must match the entire name. Blocking a namespace blocks everything inside it.
+    /// Blocked items are not generated even if an allowed item depends on them.
+    pub fn block_item<T: AsRef<str>>(mut self, pattern: T) -> Self {
+        self.block_items.push(pattern.as_ref().to_string());
+        self
+    }
+
+    /// Only generate bindings for items declared in headers whose path matches the glob `pattern`,
+    /// along with the items they depend on.
+    ///
+    /// Paths are matched as reported by `libclang`, with `/` as the separator, so patterns should
+    /// usually start with `**/` (e.g. `**/pluginterfaces/vst/*.h`). If no items or headers are
+    /// explicitly allowed, bindings are generated for all items which aren't blocked.
+    pub fn allow_header<T: AsRef<str>>(mut self, pattern: T) -> Self {
+        self.allow_headers.push(pattern.as_ref().to_string());
+        self
+    }
+
+    /// Do not generate bindings for items declared in headers whose path matches the glob
+    /// `pattern`.
+    ///
+    /// Patterns are matched in the same way as for [`allow_header`](Self::allow_header). Unlike
+    /// headers excluded from the input, blocked headers are still parsed, so items from other
+    /// headers can continue to use the declarations in them.
+    pub fn block_header<T: AsRef<str>>(mut self, pattern: T) -> Self {
+        self.block_headers.push(pattern.as_ref().to_string());
+        self
+    }
+
     /// Registers a callback for parsing constant definitions which `libclang` is not able to
     /// evaluate.
     ///
@@ -96,6 +260,9 @@ impl Generator {
 
     /// Registers a callback which should, when given the name of an interface as a string, return
     /// a string containing a Rust expression evaluating to the `Guid` value for that interface.
+    ///
+    /// The callback is only used for interfaces whose IID was not declared by one of the macros
+    /// registered via [`iid_macro`](Self::iid_macro).
     pub fn iid_generator<F>(mut self, f: F) -> Self
     where
         F: Fn(&str) -> String + 'static,
@@ -104,6 +271,29 @@ impl Generator {
         self
     }
 
+    /// Registers a macro which declares the IID of an interface.
+    ///
+    /// Declarations expanded from the macro are recognized while parsing, and the resulting IID
+    /// is attached to the corresponding [`Record`](crate::ir::Record) and used for its
+    /// `Interface::IID` constant. If any interface ends up without an IID and no
+    /// [`iid_generator`](Self::iid_generator) is set, generation fails with
+    /// [`Error::MissingIids`].
+    pub fn iid_macro(mut self, iid_macro: IidMacro) -> Self {
+        self.iid_macros.push(iid_macro);
+        self
+    }
+
+    /// Generates a constant named `{Interface}_iid` of type `type_` for each interface whose IID
+    /// was declared by one of the macros registered via [`iid_macro`](Self::iid_macro).
+    ///
+    /// `type_` should be in scope where the resulting bindings are placed, and it should be an
+    /// array of 16 integers (such as `[u8; 16]`), since the constants are initialized with array
+    /// literals.
+    pub fn iid_constant_type<T: AsRef<str>>(mut self, type_: T) -> Self {
+        self.iid_constant_type = Some(type_.as_ref().to_string());
+        self
+    }
+
     /// Registers a function which will be called by the implementations of
     /// `Unknown::query_interface` for generated interface types.
     ///
@@ -134,35 +324,411 @@ impl Generator {
         self
     }
 
+    /// Registers a callback for renaming types (structs, unions, typedefs, and enums) in the
+    /// generated bindings.
+    ///
+    /// The callback is passed the C++ name of each type, and returns the name to use instead, or
+    /// `None` to keep the C++ name. The new name is used everywhere the type is referenced, and for
+    /// the interface's `{name}Vtbl` struct and `{name}Trait` trait. Renamed items keep their C++
+    /// name as a `#[doc(alias)]`.
+    pub fn type_renamer<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + 'static,
+    {
+        self.type_renamer = Some(Box::new(f));
+        self
+    }
+
+    /// Registers a callback for renaming the methods of interface traits.
+    ///
+    /// The callback is passed the C++ names of the interface and of the method, and returns the
+    /// name to use instead, or `None` to keep the default name. Virtual table fields always keep the
+    /// C++ method names.
+    pub fn method_renamer<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &str) -> Option<String> + 'static,
+    {
+        self.method_renamer = Some(Box::new(f));
+        self
+    }
+
+    /// Registers a callback for renaming struct and union fields (including bitfield accessors).
+    ///
+    /// The callback is passed the C++ name of the record and the name of the field, and returns
+    /// the name to use instead, or `None` to keep the field name. Anonymous fields are passed
+    /// under their generated names (`__field0`, `__field1`, etc.).
+    pub fn field_renamer<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &str) -> Option<String> + 'static,
+    {
+        self.field_renamer = Some(Box::new(f));
+        self
+    }
+
+    /// Registers a callback for renaming constants, enumerators, and macro constants.
+    ///
+    /// The callback is passed the C++ name of each constant, and returns the name to use instead,
+    /// or `None` to keep the C++ name.
+    pub fn constant_renamer<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + 'static,
+    {
+        self.constant_renamer = Some(Box::new(f));
+        self
+    }
+
+    /// Registers a callback for renaming modules.
+    ///
+    /// The callback is passed the name each module would otherwise have: the C++ name for
+    /// namespaces, or `{name}_` (after any [type renaming](Self::type_renamer)) for the modules
+    /// holding an enum's enumerators or the definitions nested in a record. It returns the name to
+    /// use instead, or `None` to keep the default name.
+    pub fn module_renamer<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + 'static,
+    {
+        self.module_renamer = Some(Box::new(f));
+        self
+    }
+
+    /// Enables or disables converting interface trait method names to snake case (e.g.
+    /// `getBusCount` to `get_bus_count`).
+    ///
+    /// Names returned by the [`method_renamer`](Self::method_renamer) callback are used as-is.
+    ///
+    /// Defaults to `false`.
+    pub fn snake_case_methods(mut self, enabled: bool) -> Self {
+        self.snake_case_methods = enabled;
+        self
+    }
+
+    /// Sets how overloaded virtual methods and free functions are given unique names in the
+    /// generated bindings.
+    ///
+    /// Overloads are always laid out in the vtable order of the target's C++ ABI; the names chosen
+    /// here are independent of that order, and so are the same for every target. Overloaded free
+    /// functions keep linking against their mangled C++ symbols.
+    ///
+    /// Defaults to [`OverloadNaming::Index`].
+    pub fn overload_naming(mut self, naming: OverloadNaming) -> Self {
+        self.overload_naming = naming;
+        self
+    }
+
+    /// Sets which `libclang` diagnostics will cause [`generate`](Self::generate) to fail.
+    ///
+    /// Defaults to [`DiagnosticPolicy::Ignore`].
+    pub fn diagnostic_policy(mut self, policy: DiagnosticPolicy) -> Self {
+        self.diagnostic_policy = policy;
+        self
+    }
+
+    /// Enables or disables compile-time layout assertions in the generated bindings.
+    ///
+    /// When enabled, each generated struct and union is followed by `const` assertions checking
+    /// that its size, alignment, and field offsets match those computed by `libclang` for the
+    /// target. Each virtual table struct is checked to be the size of its primary base's virtual
+    /// table plus one pointer for each virtual method and destructor slot. The generated
+    /// assertions use `core::mem::offset_of!`, which requires Rust 1.77 or newer.
+    ///
+    /// Defaults to `false`.
+    pub fn layout_tests(mut self, enabled: bool) -> Self {
+        self.layout_tests = enabled;
+        self
+    }
+
+    /// Enables or disables generating Rust fixed-width integer types for fixed-width C++ types.
+    ///
+    /// When enabled, the `<stdint.h>` typedefs `int8_t` through `uint64_t` are generated as `i8`
+    /// through `u64`, and `char16_t` (along with `wchar_t` on targets where it is 16 bits wide) is
+    /// generated as `u16`, rather than as the C types they are defined as (e.g. `c_long`, whose
+    /// width differs between Windows and other platforms).
+    ///
+    /// Defaults to `false`.
+    pub fn fixed_width_integers(mut self, enabled: bool) -> Self {
+        self.fixed_width_integers = enabled;
+        self
+    }
+
+    /// Enables or disables generating constants for object-like `#define` macros.
+    ///
+    /// When enabled, each macro which expands to an integer, floating-point, or string literal, or
+    /// to an arithmetic expression involving such literals and other macros, is emitted as a `pub
+    /// const` at the top level of the generated bindings. Macros which cannot be evaluated, and
+    /// macros with the same name as another constant, are skipped. Use
+    /// [`allow_macro`](Self::allow_macro) and [`block_macro`](Self::block_macro) to control which
+    /// macros are included.
+    ///
+    /// Defaults to `false`.
+    pub fn macro_constants(mut self, enabled: bool) -> Self {
+        self.macro_constants = enabled;
+        self
+    }
+
+    /// Only generate constants for `macro_` and other macros passed to `allow_macro`.
+    ///
+    /// If no macros are explicitly allowed, constants are generated for all macros which aren't
+    /// blocked.
+    pub fn allow_macro<T: AsRef<str>>(mut self, macro_: T) -> Self {
+        self.allow_macros.insert(macro_.as_ref().to_string());
+        self
+    }
+
+    /// Only generate constants for `macros` and other macros passed to `allow_macro`.
+    pub fn allow_macros<'a, T: AsRef<[&'a str]>>(mut self, macros: T) -> Self {
+        self.allow_macros
+            .extend(macros.as_ref().iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Do not generate a constant for `macro_`.
+    pub fn block_macro<T: AsRef<str>>(mut self, macro_: T) -> Self {
+        self.block_macros.insert(macro_.as_ref().to_string());
+        self
+    }
+
+    /// Do not generate constants for `macros`.
+    pub fn block_macros<'a, T: AsRef<[&'a str]>>(mut self, macros: T) -> Self {
+        self.block_macros
+            .extend(macros.as_ref().iter().map(|s| s.to_string()));
+        self
+    }
+
+    /// Sets the [`Backend`] used to generate output from the parsed definitions.
+    ///
+    /// Defaults to [`RustBackend`](crate::RustBackend), which generates Rust bindings.
+    pub fn backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Some(Box::new(backend));
+        self
+    }
+
     /// Generates Rust bindings for the C++ definitions in `source` and outputs them via `sink`.
+    ///
+    /// If a custom [`backend`](Self::backend) has been set, its output is generated instead.
+    ///
+    /// This is equivalent to calling [`parse`](Self::parse) followed by
+    /// [`generate_from_ir`](Self::generate_from_ir).
+    ///
+    /// If any targets have been added via [`cfg_target`](Self::cfg_target), the input is parsed
+    /// once for each of them and [`Backend::emit_targets`] is used to generate the output.
+    ///
+    /// On success, returns all diagnostics emitted by `libclang` while parsing `source`. If any
+    /// diagnostics are disallowed by the configured [`DiagnosticPolicy`], no bindings are generated
+    /// and [`Error::Diagnostics`] is returned instead.
     pub fn generate<T: AsRef<str>, W: Write>(
         &self,
         source: T,
-        sink: W,
-    ) -> Result<(), Box<dyn Error>> {
+        mut sink: W,
+    ) -> Result<Vec<Diagnostic>, Error> {
+        if self.cfg_targets.is_empty() {
+            let (namespace, diagnostics) = self.parse(source)?;
+            self.generate_from_ir(&namespace, sink)?;
+
+            return Ok(diagnostics);
+        }
+
+        let mut namespaces = Vec::new();
+        let mut diagnostics = Vec::new();
+        for target in &self.cfg_targets {
+            let (namespace, target_diagnostics) = self.parse_target(target, source.as_ref())?;
+            namespaces.push((target.clone(), namespace));
+            diagnostics.extend(target_diagnostics);
+        }
+
+        match &self.backend {
+            Some(backend) => backend.emit_targets(self, &namespaces, &mut sink)?,
+            None => RustBackend.emit_targets(self, &namespaces, &mut sink)?,
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// Parses the C++ definitions in `source` into the intermediate representation used for
+    /// generating bindings, without generating any bindings.
+    ///
+    /// On success, returns the global namespace along with all diagnostics emitted by `libclang`
+    /// while parsing `source`. If any diagnostics are disallowed by the configured
+    /// [`DiagnosticPolicy`], [`Error::Diagnostics`] is returned instead.
+    ///
+    /// The input is parsed for the [`target`](Self::target) only, regardless of any targets added
+    /// via [`cfg_target`](Self::cfg_target).
+    pub fn parse<T: AsRef<str>>(&self, source: T) -> Result<(Namespace, Vec<Diagnostic>), Error> {
+        self.parse_target(&self.target_triple(), source.as_ref())
+    }
+
+    fn parse_target(
+        &self,
+        target: &str,
+        source: &str,
+    ) -> Result<(Namespace, Vec<Diagnostic>), Error> {
         if !clang_sys::is_loaded() {
-            clang_sys::load()?;
+            clang_sys::load().map_err(Error::ClangLoad)?;
         }
 
-        let mut clang_target = None;
-        if let Ok(target) = env::var("TARGET") {
-            if target != HOST_TARGET {
-                clang_target = Some(rust_to_clang_target(&target));
+        // clang-sys declares functions from newer versions of libclang, which are checked with
+        // `is_loaded()` before use. Anything older than the documented minimum is rejected here.
+        match clang_sys::get_library().and_then(|library| library.version()) {
+            Some(version) if version >= clang_sys::Version::V6_0 => {}
+            version => {
+                let found =
+                    version.map_or_else(|| String::from("an older version"), |v| v.to_string());
+                return Err(Error::ClangLoad(format!(
+                    "libclang 6.0 or later is required, found {found}"
+                )));
             }
         }
 
+        let clang_target = (target != HOST_TARGET).then(|| rust_to_clang_target(target));
+
+        let mut input = String::new();
+        for header in &self.headers {
+            // Use forward slashes so that the input is the same on every platform
+            let path = header.to_string_lossy().replace('\\', "/");
+            input.push_str(&format!("#include \"{path}\"\n"));
+        }
+        input.push_str(source);
+
+        let mut args = Vec::new();
+        if let Some(std) = &self.std {
+            args.push(format!("-std={std}"));
+        }
+        args.extend(self.clang_args.iter().cloned());
+
         let unit = TranslationUnit::new(
-            source.as_ref(),
+            &input,
             &self.include_paths,
             clang_target.as_deref(),
-        )
-        .unwrap();
+            &args,
+            self.macro_constants,
+        )?;
+
+        let diagnostics = unit.diagnostics();
+        let disallowed = self.diagnostic_policy.disallowed(&diagnostics);
+        if !disallowed.is_empty() {
+            return Err(Error::Diagnostics(disallowed));
+        }
+
+        let namespace = Namespace::parse(&unit.cursor(), self, target)?;
+
+        Ok((namespace, diagnostics))
+    }
+
+    /// Generates Rust bindings (or the output of the configured [`backend`](Self::backend)) from
+    /// a previously parsed (and possibly modified) [`Namespace`] and outputs them via `sink`.
+    ///
+    /// Options which affect parsing (such as include paths and skipped types) have no effect here;
+    /// options which affect code generation (such as the interface trait and `Unknown` function
+    /// options) are applied as usual.
+    pub fn generate_from_ir<W: Write>(
+        &self,
+        namespace: &Namespace,
+        mut sink: W,
+    ) -> Result<(), Error> {
+        match &self.backend {
+            Some(backend) => backend.emit(self, namespace, &mut sink),
+            None => RustBackend.emit(self, namespace, &mut sink),
+        }
+    }
+
+    /// Returns the `rustc` target triple for which bindings are generated.
+    pub fn target_triple(&self) -> String {
+        self.target
+            .clone()
+            .or_else(|| env::var("TARGET").ok())
+            .unwrap_or_else(|| HOST_TARGET.to_string())
+    }
+}
+
+/// Accessors for the options which affect code generation, for use by [`Backend`]
+/// implementations.
+impl Generator {
+    /// Returns the targets added via [`cfg_target`](Self::cfg_target).
+    pub fn get_cfg_targets(&self) -> &[String] {
+        &self.cfg_targets
+    }
 
-        let namespace = Namespace::parse(&unit.cursor(), &self)?;
+    /// Returns `true` if `interface` was passed to
+    /// [`skip_interface_trait`](Self::skip_interface_trait).
+    pub fn is_interface_trait_skipped(&self, interface: &str) -> bool {
+        self.skip_interface_traits.contains(interface)
+    }
+
+    /// Returns the Rust type registered for the qualified C++ type name `type_` via
+    /// [`map_type`](Self::map_type), if any.
+    pub fn get_mapped_type(&self, type_: &str) -> Option<&str> {
+        self.type_map.get(type_).map(String::as_str)
+    }
+
+    /// Returns the expression for the IID of `interface` produced by the
+    /// [`iid_generator`](Self::iid_generator) callback, or `None` if none is registered.
+    pub fn get_iid_expr(&self, interface: &str) -> Option<String> {
+        self.iid_generator.as_ref().map(|f| f(interface))
+    }
+
+    /// Returns the type set via [`iid_constant_type`](Self::iid_constant_type), if any.
+    pub fn get_iid_constant_type(&self) -> Option<&str> {
+        self.iid_constant_type.as_deref()
+    }
+
+    /// Returns the function set via [`query_interface_fn`](Self::query_interface_fn), if any.
+    pub fn get_query_interface_fn(&self) -> Option<&str> {
+        self.query_interface_fn.as_deref()
+    }
+
+    /// Returns the function set via [`add_ref_fn`](Self::add_ref_fn), if any.
+    pub fn get_add_ref_fn(&self) -> Option<&str> {
+        self.add_ref_fn.as_deref()
+    }
+
+    /// Returns the function set via [`release_fn`](Self::release_fn), if any.
+    pub fn get_release_fn(&self) -> Option<&str> {
+        self.release_fn.as_deref()
+    }
 
-        let mut printer = RustPrinter::new(sink, &self);
-        printer.print_namespace(&namespace)?;
+    /// Returns the new name for the type `name` from the [`type_renamer`](Self::type_renamer)
+    /// callback, or `None` if it keeps its C++ name.
+    pub fn rename_type(&self, name: &str) -> Option<String> {
+        self.type_renamer.as_ref().and_then(|f| f(name))
+    }
+
+    /// Returns the new name for `method` of `interface` from the
+    /// [`method_renamer`](Self::method_renamer) callback, or `None` if it keeps its default name.
+    pub fn rename_method(&self, interface: &str, method: &str) -> Option<String> {
+        self.method_renamer
+            .as_ref()
+            .and_then(|f| f(interface, method))
+    }
+
+    /// Returns the new name for `field` of `record` from the
+    /// [`field_renamer`](Self::field_renamer) callback, or `None` if it keeps its name.
+    pub fn rename_field(&self, record: &str, field: &str) -> Option<String> {
+        self.field_renamer.as_ref().and_then(|f| f(record, field))
+    }
+
+    /// Returns the new name for the constant `name` from the
+    /// [`constant_renamer`](Self::constant_renamer) callback, or `None` if it keeps its C++ name.
+    pub fn rename_constant(&self, name: &str) -> Option<String> {
+        self.constant_renamer.as_ref().and_then(|f| f(name))
+    }
+
+    /// Returns the new name for the module `name` from the
+    /// [`module_renamer`](Self::module_renamer) callback, or `None` if it keeps its default name.
+    pub fn rename_module(&self, name: &str) -> Option<String> {
+        self.module_renamer.as_ref().and_then(|f| f(name))
+    }
+
+    /// Returns whether [`snake_case_methods`](Self::snake_case_methods) is enabled.
+    pub fn get_snake_case_methods(&self) -> bool {
+        self.snake_case_methods
+    }
+
+    /// Returns the scheme set via [`overload_naming`](Self::overload_naming).
+    pub fn get_overload_naming(&self) -> OverloadNaming {
+        self.overload_naming
+    }
 
-        Ok(())
+    /// Returns whether [`layout_tests`](Self::layout_tests) is enabled.
+    pub fn get_layout_tests(&self) -> bool {
+        self.layout_tests
     }
 }
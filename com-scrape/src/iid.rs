@@ -0,0 +1,160 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Describes a macro which declares the IID of an interface, such as the VST 3 SDK's
+/// `DECLARE_CLASS_IID(IPluginBase, 0x22888DDB, 0x156E45AE, 0x8358B348, 0x08190625)`.
+///
+/// When a declaration is expanded from a registered IID macro (see [`Generator::iid_macro`]), the
+/// macro's arguments are evaluated and the resulting IID is used for the `Interface::IID` constant
+/// of the named interface.
+///
+/// [`Generator::iid_macro`]: crate::Generator::iid_macro
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(deny_unknown_fields, rename_all = "kebab-case")
+)]
+pub struct IidMacro {
+    /// The name of the macro.
+    pub name: String,
+    /// The index of the argument naming the interface.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub interface_arg: usize,
+    /// The index of the first argument making up the IID value. The number of arguments is
+    /// determined by `style`.
+    #[cfg_attr(feature = "serde", serde(default = "default_value_arg"))]
+    pub value_arg: usize,
+    /// How the IID is constructed from the macro arguments.
+    pub style: IidStyle,
+}
+
+#[cfg(feature = "serde")]
+fn default_value_arg() -> usize {
+    1
+}
+
+impl IidMacro {
+    /// Creates an `IidMacro` for a macro whose first argument is the interface name and whose
+    /// remaining arguments make up the IID.
+    pub fn new<T: AsRef<str>>(name: T, style: IidStyle) -> IidMacro {
+        IidMacro {
+            name: name.as_ref().to_string(),
+            interface_arg: 0,
+            value_arg: 1,
+            style,
+        }
+    }
+}
+
+/// The ways in which an [`IidMacro`] can construct a 16-byte IID from its arguments.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum IidStyle {
+    /// Sixteen byte values, in memory order.
+    Bytes,
+    /// A 32-bit integer, two 16-bit integers, and eight byte values, laid out like the Windows
+    /// `GUID` struct (as used by `DEFINE_GUID`).
+    Guid,
+    /// Four 32-bit integers, each stored in big-endian byte order.
+    Words,
+    /// Four 32-bit integers, laid out like a `GUID` on Windows targets (the second integer
+    /// providing the two 16-bit fields) and like [`Words`](Self::Words) on other targets. This
+    /// matches the `INLINE_UID` macro of the VST 3 SDK.
+    PlatformWords,
+}
+
+impl IidStyle {
+    /// The maximum value of each argument.
+    pub(crate) fn arg_limits(self) -> &'static [u64] {
+        const BYTE: u64 = u8::MAX as u64;
+        const SHORT: u64 = u16::MAX as u64;
+        const WORD: u64 = u32::MAX as u64;
+
+        match self {
+            IidStyle::Bytes => &[BYTE; 16],
+            IidStyle::Guid => &[
+                WORD, SHORT, SHORT, BYTE, BYTE, BYTE, BYTE, BYTE, BYTE, BYTE, BYTE,
+            ],
+            IidStyle::Words | IidStyle::PlatformWords => &[WORD; 4],
+        }
+    }
+
+    /// Constructs an IID from argument values, which must satisfy the limits given by
+    /// `arg_limits`.
+    pub(crate) fn to_bytes(self, args: &[u64], windows: bool) -> [u8; 16] {
+        let mut bytes = Vec::with_capacity(16);
+
+        match self {
+            IidStyle::Bytes => bytes.extend(args.iter().map(|&arg| arg as u8)),
+            IidStyle::Guid => {
+                bytes.extend_from_slice(&(args[0] as u32).to_le_bytes());
+                bytes.extend_from_slice(&(args[1] as u16).to_le_bytes());
+                bytes.extend_from_slice(&(args[2] as u16).to_le_bytes());
+                bytes.extend(args[3..].iter().map(|&arg| arg as u8));
+            }
+            IidStyle::PlatformWords if windows => {
+                let b = args[1] as u32;
+                bytes.extend_from_slice(&(args[0] as u32).to_le_bytes());
+                bytes.extend_from_slice(&((b >> 16) as u16).to_le_bytes());
+                bytes.extend_from_slice(&(b as u16).to_le_bytes());
+                bytes.extend_from_slice(&(args[2] as u32).to_be_bytes());
+                bytes.extend_from_slice(&(args[3] as u32).to_be_bytes());
+            }
+            IidStyle::Words | IidStyle::PlatformWords => {
+                for &arg in args {
+                    bytes.extend_from_slice(&(arg as u32).to_be_bytes());
+                }
+            }
+        }
+
+        bytes.try_into().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FUNKNOWN: [u64; 4] = [0x00000000, 0x00000000, 0xC0000000, 0x00000046];
+    const IPLUGINBASE: [u64; 4] = [0x22888DDB, 0x156E45AE, 0x8358B348, 0x08190625];
+
+    #[test]
+    fn words() {
+        assert_eq!(
+            IidStyle::Words.to_bytes(&IPLUGINBASE, true),
+            [
+                0x22, 0x88, 0x8D, 0xDB, 0x15, 0x6E, 0x45, 0xAE, 0x83, 0x58, 0xB3, 0x48, 0x08, 0x19,
+                0x06, 0x25
+            ],
+        );
+        assert_eq!(
+            IidStyle::PlatformWords.to_bytes(&IPLUGINBASE, false),
+            IidStyle::Words.to_bytes(&IPLUGINBASE, false),
+        );
+    }
+
+    #[test]
+    fn platform_words_windows() {
+        assert_eq!(
+            IidStyle::PlatformWords.to_bytes(&IPLUGINBASE, true),
+            [
+                0xDB, 0x8D, 0x88, 0x22, 0x6E, 0x15, 0xAE, 0x45, 0x83, 0x58, 0xB3, 0x48, 0x08, 0x19,
+                0x06, 0x25
+            ],
+        );
+
+        // FUnknown shares its IID with COM's IUnknown on Windows
+        let iunknown = IidStyle::Guid.to_bytes(
+            &[
+                0x00000000, 0x0000, 0x0000, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+            ],
+            true,
+        );
+        assert_eq!(IidStyle::PlatformWords.to_bytes(&FUNKNOWN, true), iunknown);
+    }
+}
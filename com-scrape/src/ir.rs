@@ -0,0 +1,374 @@
+//! The intermediate representation of parsed C++ definitions.
+//!
+//! [`Generator::parse`](crate::Generator::parse) produces a [`Namespace`] from C++ headers, and
+//! [`Generator::generate_from_ir`](crate::Generator::generate_from_ir) generates Rust bindings from
+//! one. In between, the IR can be inspected or modified, and with the `serde` feature enabled it
+//! can be serialized (e.g. to JSON via `serde_json`) and cached.
+
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A C++ namespace, or the global namespace of a translation unit.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Namespace {
+    /// Nested namespaces, by name.
+    pub children: BTreeMap<String, Namespace>,
+    pub typedefs: Vec<Typedef>,
+    pub records: Vec<Record>,
+    pub opaque_records: Vec<OpaqueRecord>,
+    pub functions: Vec<Function>,
+    pub constants: Vec<Constant>,
+    /// Rust source for constants produced by
+    /// [`Generator::constant_parser`](crate::Generator::constant_parser).
+    pub unparsed_constants: Vec<String>,
+}
+
+impl Namespace {
+    pub fn new() -> Namespace {
+        Namespace {
+            children: BTreeMap::new(),
+            typedefs: Vec::new(),
+            records: Vec::new(),
+            opaque_records: Vec::new(),
+            functions: Vec::new(),
+            constants: Vec::new(),
+            unparsed_constants: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the namespace and all of its children contain no definitions.
+    pub fn is_empty(&self) -> bool {
+        self.typedefs.is_empty()
+            && self.records.is_empty()
+            && self.opaque_records.is_empty()
+            && self.functions.is_empty()
+            && self.constants.is_empty()
+            && self.unparsed_constants.is_empty()
+            && self.children.values().all(|child| child.is_empty())
+    }
+}
+
+/// A type alias or a named enum. For enums, `type_` is the underlying integer type and `inner`
+/// holds the enumerators.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Typedef {
+    pub name: String,
+    pub doc: Option<String>,
+    pub type_: Type,
+    pub inner: Namespace,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RecordKind {
+    Struct,
+    Union,
+}
+
+/// A struct, class, or union definition. Records with virtual methods are COM interfaces.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Record {
+    pub name: String,
+    pub doc: Option<String>,
+    pub kind: RecordKind,
+    pub size: usize,
+    pub align: usize,
+    /// If the record is packed (e.g. via `#pragma pack`), the maximum alignment of its fields.
+    pub packed: Option<usize>,
+    /// If the record has a greater alignment than its fields require (e.g. via `alignas`), its
+    /// alignment.
+    pub explicit_align: Option<usize>,
+    pub fields: Vec<Field>,
+    pub bases: Vec<Base>,
+    /// Virtual methods introduced by the record, in vtable order for the target.
+    pub virtual_methods: Vec<Method>,
+    /// The virtual destructor introduced by the record, if any. Destructors which override one
+    /// declared in a base class don't occupy vtable slots of their own, and are not included.
+    pub virtual_destructor: Option<VirtualDestructor>,
+    /// For interfaces, the IID declared via an [`IidMacro`](crate::IidMacro), if any.
+    pub iid: Option<[u8; 16]>,
+    /// Definitions nested inside the record.
+    pub inner: Namespace,
+}
+
+impl Record {
+    /// Returns `true` if the record introduces virtual methods or a virtual destructor, in which
+    /// case it is generated as a COM interface.
+    pub fn is_interface(&self) -> bool {
+        !self.virtual_methods.is_empty() || self.virtual_destructor.is_some()
+    }
+}
+
+/// A record whose layout is not exposed, either because it is declared but never defined or
+/// because it was configured via [`Generator::opaque_type`](crate::Generator::opaque_type).
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OpaqueRecord {
+    pub name: String,
+    pub doc: Option<String>,
+}
+
+/// A C++ ABI, which determines details of the vtable layout.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Abi {
+    /// The Itanium C++ ABI, used on all targets except Windows MSVC targets.
+    Itanium,
+    /// The MSVC C++ ABI.
+    Msvc,
+}
+
+/// The vtable slots occupied by a virtual destructor.
+///
+/// Under the Itanium ABI, a virtual destructor occupies two slots (the complete object destructor
+/// followed by the deleting destructor); under the MSVC ABI, it occupies a single slot (the scalar
+/// deleting destructor, which takes an additional `flags` argument).
+///
+/// The generated implementations never destroy the object directly: the Itanium complete object
+/// destructor does nothing, and the deleting destructor releases a reference, so `delete p` from
+/// C++ decrements the reference count rather than destroying the object.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VirtualDestructor {
+    /// The index in [`Record::virtual_methods`] of the method preceded by the destructor's slots.
+    pub position: usize,
+    pub abi: Abi,
+    /// The calling convention of the destructor's slots (`thiscall` on 32-bit MSVC targets, `C`
+    /// elsewhere).
+    pub calling_convention: CallingConvention,
+}
+
+/// A base class of a record, along with its own base classes.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Base {
+    pub name: String,
+    /// Offset in bytes of the base class subobject within the derived class.
+    pub offset: usize,
+    pub bases: Vec<Base>,
+}
+
+/// A field of a record. Anonymous unions are represented as unnamed fields.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Field {
+    pub name: Option<String>,
+    pub doc: Option<String>,
+    /// Offset in bits of the field within its record, if known.
+    pub bit_offset: Option<usize>,
+    /// Size in bytes of the field's type.
+    pub size: usize,
+    pub bitfield: Option<Bitfield>,
+    pub type_: Type,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Bitfield {
+    /// Width of the bitfield in bits.
+    pub width: usize,
+    /// Whether the bitfield's type is a signed integer type.
+    pub signed: bool,
+}
+
+/// A virtual method of a record.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Method {
+    pub name: String,
+    pub doc: Option<String>,
+    pub arguments: Vec<Argument>,
+    pub result_type: Type,
+    /// For overloaded methods, the index of this overload among those with the same name, in
+    /// declaration order.
+    pub overload: Option<usize>,
+    /// Whether the result is returned through a hidden pointer argument, and where that argument
+    /// is passed. The hidden pointer is also returned.
+    pub indirect_result: Option<IndirectResult>,
+}
+
+/// The position of the hidden pointer argument through which a method returns its result.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IndirectResult {
+    /// The pointer precedes `this`, as in the Itanium ABI, which returns records that are not
+    /// trivially copyable or destructible this way.
+    BeforeThis,
+    /// The pointer follows `this`, as in the MSVC ABI, which returns all records from methods
+    /// this way.
+    AfterThis,
+}
+
+/// A free function declaration.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Function {
+    pub name: String,
+    pub doc: Option<String>,
+    /// The symbol name to link against, if it differs from `name` (e.g. for functions with C++
+    /// linkage).
+    pub link_name: Option<String>,
+    pub calling_convention: CallingConvention,
+    pub arguments: Vec<Argument>,
+    pub result_type: Type,
+    /// For overloaded functions, the index of this overload among those with the same name in
+    /// the same namespace, in declaration order.
+    pub overload: Option<usize>,
+}
+
+/// The calling convention of a free function.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CallingConvention {
+    /// The platform's default C calling convention.
+    C,
+    /// `__stdcall`, the default for Win32 API functions on x86.
+    Stdcall,
+    /// `__fastcall` on x86.
+    Fastcall,
+    /// `__thiscall` on x86.
+    Thiscall,
+    /// The Windows x86-64 calling convention, used on other targets via `__attribute__((ms_abi))`.
+    Win64,
+    /// The System V x86-64 calling convention, used on Windows via `__attribute__((sysv_abi))`.
+    SysV64,
+}
+
+/// An argument of a method or function. Unnamed arguments have an empty name.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Argument {
+    pub name: String,
+    pub type_: Type,
+}
+
+/// A constant, enumerator, or macro constant.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Constant {
+    pub name: String,
+    pub doc: Option<String>,
+    pub type_: Type,
+    pub value: Value,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Type {
+    Void,
+    Bool,
+    Char,
+    UChar,
+    UShort,
+    UInt,
+    ULong,
+    ULongLong,
+    SChar,
+    Short,
+    Int,
+    Long,
+    LongLong,
+    /// An unsigned integer of the given size in bytes.
+    Unsigned(usize),
+    /// A signed integer of the given size in bytes.
+    Signed(usize),
+    Float,
+    Double,
+    Pointer {
+        is_const: bool,
+        pointee: Box<Type>,
+    },
+    Reference {
+        is_const: bool,
+        pointee: Box<Type>,
+    },
+    /// A named record, referred to by name and by the path of the namespace which declares it
+    /// (e.g. `["Steinberg", "Vst"]`). Records nested in another record have the path of the
+    /// namespace enclosing the outermost record.
+    Record {
+        name: String,
+        namespace: Vec<String>,
+    },
+    /// An anonymous record, which is defined inline.
+    UnnamedRecord(Box<Record>),
+    /// A typedef or enum, referred to by name and by the path of the namespace which declares
+    /// it, as for [`Type::Record`].
+    Typedef {
+        name: String,
+        namespace: Vec<String>,
+    },
+    Array(usize, Box<Type>),
+    Function {
+        calling_convention: CallingConvention,
+        arguments: Vec<Type>,
+        result_type: Box<Type>,
+    },
+    /// A typedef or record which has been replaced with a user-provided Rust type via
+    /// [`Generator::map_type`](crate::Generator::map_type).
+    Mapped {
+        /// The qualified C++ name of the replaced type.
+        name: String,
+        /// The Rust type used in its place.
+        rust_type: String,
+        /// Size in bytes of the C++ type.
+        size: usize,
+        /// Alignment in bytes of the C++ type.
+        align: usize,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Value {
+    Signed(i64),
+    Unsigned(u64),
+    Float(f64),
+    Str(String),
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let mut namespace = Namespace::new();
+        namespace.constants.push(Constant {
+            name: String::from("kValue"),
+            doc: Some(String::from("A value.")),
+            type_: Type::Typedef {
+                name: String::from("int32"),
+                namespace: vec![String::from("Steinberg")],
+            },
+            value: Value::Signed(-1),
+        });
+        namespace.functions.push(Function {
+            name: String::from("GetPluginFactory"),
+            doc: None,
+            link_name: None,
+            calling_convention: CallingConvention::Stdcall,
+            arguments: Vec::new(),
+            result_type: Type::Pointer {
+                is_const: false,
+                pointee: Box::new(Type::Record {
+                    name: String::from("IPluginFactory"),
+                    namespace: vec![String::from("Steinberg")],
+                }),
+            },
+            overload: None,
+        });
+
+        let mut root = Namespace::new();
+        root.children.insert(String::from("Steinberg"), namespace);
+
+        let json = serde_json::to_string(&root).unwrap();
+        let parsed: Namespace = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(format!("{parsed:?}"), format!("{root:?}"));
+    }
+}
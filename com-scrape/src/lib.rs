@@ -2,9 +2,30 @@
 //! C++. `com-scrape` is developed specifically for use in the `vst3-bindgen` crate, and as such,
 //! robustness for arbitrary C++ inputs is a non-goal.
 
+mod backend;
 mod clang;
+#[cfg(feature = "config")]
+mod config;
+mod diagnostic;
+mod doc;
+mod error;
+mod filter;
 mod generator;
+mod iid;
+pub mod ir;
+mod macros;
+mod overload;
 mod parse;
 mod print;
+mod targets;
+#[cfg(test)]
+mod test_util;
 
+pub use backend::{Backend, RustBackend};
+#[cfg(feature = "config")]
+pub use config::Config;
+pub use diagnostic::{Diagnostic, DiagnosticPolicy, Severity};
+pub use error::Error;
 pub use generator::Generator;
+pub use iid::{IidMacro, IidStyle};
+pub use overload::OverloadNaming;
@@ -0,0 +1,616 @@
+use std::collections::{HashMap, HashSet};
+
+/// The value of an object-like macro which expands to a constant expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MacroValue {
+    /// An integer, along with whether its type is unsigned and whether it is 64 bits wide (as
+    /// opposed to 32 bits wide).
+    Int {
+        value: i128,
+        unsigned: bool,
+        wide: bool,
+    },
+    /// A floating-point number, along with whether its type is `float` (as opposed to `double`).
+    Float {
+        value: f64,
+        single: bool,
+    },
+    Str(String),
+}
+
+impl MacroValue {
+    fn int(value: i128, unsigned: bool, wide: bool) -> MacroValue {
+        MacroValue::Int {
+            value: wrap(value, unsigned, wide),
+            unsigned,
+            wide,
+        }
+    }
+
+    fn bool(value: bool) -> MacroValue {
+        MacroValue::int(value as i128, false, false)
+    }
+}
+
+/// Truncates `value` to the width of the given integer type, interpreting it as signed or
+/// unsigned.
+fn wrap(value: i128, unsigned: bool, wide: bool) -> i128 {
+    match (unsigned, wide) {
+        (false, false) => value as i32 as i128,
+        (true, false) => value as u32 as i128,
+        (false, true) => value as i64 as i128,
+        (true, true) => value as u64 as i128,
+    }
+}
+
+/// Evaluates object-like macros, given the token lists of all macro definitions. Macros may refer
+/// to other macros, regardless of the order in which they were defined.
+pub struct Evaluator<'a> {
+    definitions: &'a HashMap<String, Vec<String>>,
+    cache: HashMap<String, Option<MacroValue>>,
+    in_progress: HashSet<String>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(definitions: &'a HashMap<String, Vec<String>>) -> Evaluator<'a> {
+        Evaluator {
+            definitions,
+            cache: HashMap::new(),
+            in_progress: HashSet::new(),
+        }
+    }
+
+    /// Evaluates the macro `name`, returning `None` if it is undefined or does not expand to a
+    /// supported constant expression.
+    pub fn evaluate(&mut self, name: &str) -> Option<MacroValue> {
+        if let Some(value) = self.cache.get(name) {
+            return value.clone();
+        }
+
+        // Guard against self-referential macros.
+        if !self.in_progress.insert(name.to_string()) {
+            return None;
+        }
+
+        let definitions = self.definitions;
+        let value = definitions
+            .get(name)
+            .and_then(|tokens| self.evaluate_tokens(tokens));
+
+        self.in_progress.remove(name);
+        self.cache.insert(name.to_string(), value.clone());
+
+        value
+    }
+
+    /// Evaluates the constant expression consisting of `tokens`, which may refer to macros.
+    pub fn evaluate_tokens(&mut self, tokens: &'a [String]) -> Option<MacroValue> {
+        let mut parser = ExprParser {
+            evaluator: self,
+            tokens,
+            pos: 0,
+        };
+        let value = parser.expr(0)?;
+        (parser.pos == tokens.len()).then_some(value)
+    }
+}
+
+#[derive(Copy, Clone)]
+enum BinaryOp {
+    LogicalOr,
+    LogicalAnd,
+    Or,
+    Xor,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Shl,
+    Shr,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinaryOp {
+    fn from_token(token: &str) -> Option<(BinaryOp, u32)> {
+        let op = match token {
+            "||" => (BinaryOp::LogicalOr, 1),
+            "&&" => (BinaryOp::LogicalAnd, 2),
+            "|" => (BinaryOp::Or, 3),
+            "^" => (BinaryOp::Xor, 4),
+            "&" => (BinaryOp::And, 5),
+            "==" => (BinaryOp::Eq, 6),
+            "!=" => (BinaryOp::Ne, 6),
+            "<" => (BinaryOp::Lt, 7),
+            "<=" => (BinaryOp::Le, 7),
+            ">" => (BinaryOp::Gt, 7),
+            ">=" => (BinaryOp::Ge, 7),
+            "<<" => (BinaryOp::Shl, 8),
+            ">>" => (BinaryOp::Shr, 8),
+            "+" => (BinaryOp::Add, 9),
+            "-" => (BinaryOp::Sub, 9),
+            "*" => (BinaryOp::Mul, 10),
+            "/" => (BinaryOp::Div, 10),
+            "%" => (BinaryOp::Rem, 10),
+            _ => return None,
+        };
+
+        Some(op)
+    }
+}
+
+/// A precedence-climbing parser for C constant expressions, which evaluates the expression as it
+/// is parsed.
+struct ExprParser<'e, 'a> {
+    evaluator: &'e mut Evaluator<'a>,
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl<'e, 'a> ExprParser<'e, 'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).map(|token| token.as_str())
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.peek()?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn expr(&mut self, min_precedence: u32) -> Option<MacroValue> {
+        let mut lhs = self.unary()?;
+
+        while let Some((op, precedence)) = self.peek().and_then(BinaryOp::from_token) {
+            if precedence <= min_precedence {
+                break;
+            }
+            self.pos += 1;
+
+            let rhs = self.expr(precedence)?;
+            lhs = binary(op, lhs, rhs)?;
+        }
+
+        Some(lhs)
+    }
+
+    fn unary(&mut self) -> Option<MacroValue> {
+        match self.peek()? {
+            "-" => {
+                self.pos += 1;
+                match self.unary()? {
+                    MacroValue::Int {
+                        value,
+                        unsigned,
+                        wide,
+                    } => Some(MacroValue::int(-value, unsigned, wide)),
+                    MacroValue::Float { value, single } => Some(MacroValue::Float {
+                        value: -value,
+                        single,
+                    }),
+                    MacroValue::Str(_) => None,
+                }
+            }
+            "+" => {
+                self.pos += 1;
+                match self.unary()? {
+                    MacroValue::Str(_) => None,
+                    value => Some(value),
+                }
+            }
+            "~" => {
+                self.pos += 1;
+                match self.unary()? {
+                    MacroValue::Int {
+                        value,
+                        unsigned,
+                        wide,
+                    } => Some(MacroValue::int(!value, unsigned, wide)),
+                    _ => None,
+                }
+            }
+            "!" => {
+                self.pos += 1;
+                match self.unary()? {
+                    MacroValue::Int { value, .. } => Some(MacroValue::bool(value == 0)),
+                    MacroValue::Float { value, .. } => Some(MacroValue::bool(value == 0.0)),
+                    MacroValue::Str(_) => None,
+                }
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Option<MacroValue> {
+        let token = self.next()?;
+
+        if token == "(" {
+            let value = self.expr(0)?;
+            return (self.next()? == ")").then_some(value);
+        }
+
+        let first = token.chars().next()?;
+        if first.is_ascii_digit() || first == '.' {
+            return parse_number(token);
+        }
+        if first == '\'' {
+            return parse_char(token);
+        }
+
+        // Adjacent string literals (including macros which expand to string literals) are
+        // concatenated.
+        let mut string: Option<String> = None;
+        let mut token = Some(token);
+        while let Some(current) = token {
+            let value = if current.starts_with('"') {
+                MacroValue::Str(parse_string(current)?)
+            } else if current.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+                self.evaluator.evaluate(current)?
+            } else {
+                return None;
+            };
+
+            match (value, &mut string) {
+                (MacroValue::Str(value), Some(string)) => string.push_str(&value),
+                (MacroValue::Str(value), None) => string = Some(value),
+                (value, None) => return Some(value),
+                (_, Some(_)) => return None,
+            }
+
+            token = self
+                .peek()
+                .filter(|next| next.starts_with('"') || self.is_string_macro(next));
+            if token.is_some() {
+                self.pos += 1;
+            }
+        }
+
+        string.map(MacroValue::Str)
+    }
+
+    fn is_string_macro(&mut self, name: &str) -> bool {
+        name.starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && matches!(self.evaluator.evaluate(name), Some(MacroValue::Str(_)))
+    }
+}
+
+fn binary(op: BinaryOp, lhs: MacroValue, rhs: MacroValue) -> Option<MacroValue> {
+    use MacroValue::*;
+
+    match (lhs, rhs) {
+        (
+            Int {
+                value: a,
+                unsigned: a_unsigned,
+                wide: a_wide,
+            },
+            Int {
+                value: b,
+                unsigned: b_unsigned,
+                wide: b_wide,
+            },
+        ) => {
+            // Shifts take the type of their left operand.
+            if let BinaryOp::Shl | BinaryOp::Shr = op {
+                let bits = if a_wide { 64 } else { 32 };
+                if !(0..bits).contains(&b) {
+                    return None;
+                }
+
+                let value = match op {
+                    BinaryOp::Shl => a << b,
+                    _ => a >> b,
+                };
+                return Some(MacroValue::int(value, a_unsigned, a_wide));
+            }
+
+            // Apply the usual arithmetic conversions. A 64-bit signed type can represent every
+            // 32-bit unsigned value, so the signedness of the wider operand wins.
+            let wide = a_wide || b_wide;
+            let unsigned = match (a_wide, b_wide) {
+                (true, false) => a_unsigned,
+                (false, true) => b_unsigned,
+                _ => a_unsigned || b_unsigned,
+            };
+            let a = wrap(a, unsigned, wide);
+            let b = wrap(b, unsigned, wide);
+
+            let value = match op {
+                BinaryOp::LogicalOr => return Some(MacroValue::bool(a != 0 || b != 0)),
+                BinaryOp::LogicalAnd => return Some(MacroValue::bool(a != 0 && b != 0)),
+                BinaryOp::Eq => return Some(MacroValue::bool(a == b)),
+                BinaryOp::Ne => return Some(MacroValue::bool(a != b)),
+                BinaryOp::Lt => return Some(MacroValue::bool(a < b)),
+                BinaryOp::Le => return Some(MacroValue::bool(a <= b)),
+                BinaryOp::Gt => return Some(MacroValue::bool(a > b)),
+                BinaryOp::Ge => return Some(MacroValue::bool(a >= b)),
+                BinaryOp::Or => a | b,
+                BinaryOp::Xor => a ^ b,
+                BinaryOp::And => a & b,
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a.checked_mul(b)?,
+                BinaryOp::Div => a.checked_div(b)?,
+                BinaryOp::Rem => a.checked_rem(b)?,
+                BinaryOp::Shl | BinaryOp::Shr => unreachable!(),
+            };
+
+            Some(MacroValue::int(value, unsigned, wide))
+        }
+        (Str(_), _) | (_, Str(_)) => None,
+        (lhs, rhs) => {
+            let (a, a_single) = as_float(lhs);
+            let (b, b_single) = as_float(rhs);
+            let single = a_single && b_single;
+
+            let value = match op {
+                BinaryOp::LogicalOr => return Some(MacroValue::bool(a != 0.0 || b != 0.0)),
+                BinaryOp::LogicalAnd => return Some(MacroValue::bool(a != 0.0 && b != 0.0)),
+                BinaryOp::Eq => return Some(MacroValue::bool(a == b)),
+                BinaryOp::Ne => return Some(MacroValue::bool(a != b)),
+                BinaryOp::Lt => return Some(MacroValue::bool(a < b)),
+                BinaryOp::Le => return Some(MacroValue::bool(a <= b)),
+                BinaryOp::Gt => return Some(MacroValue::bool(a > b)),
+                BinaryOp::Ge => return Some(MacroValue::bool(a >= b)),
+                BinaryOp::Add => a + b,
+                BinaryOp::Sub => a - b,
+                BinaryOp::Mul => a * b,
+                BinaryOp::Div => a / b,
+                _ => return None,
+            };
+
+            Some(MacroValue::Float { value, single })
+        }
+    }
+}
+
+/// Converts an integer or floating-point value to `f64`. Integers are treated as single-precision
+/// so that they don't widen `float` operands.
+fn as_float(value: MacroValue) -> (f64, bool) {
+    match value {
+        MacroValue::Int { value, .. } => (value as f64, true),
+        MacroValue::Float { value, single } => (value, single),
+        MacroValue::Str(_) => unreachable!(),
+    }
+}
+
+fn parse_number(token: &str) -> Option<MacroValue> {
+    let token = token.replace('\'', "");
+    let lower = token.to_ascii_lowercase();
+
+    let is_hex = lower.starts_with("0x");
+    let is_float = if is_hex {
+        lower.contains('p')
+    } else {
+        lower.contains(['.', 'e'])
+    };
+
+    if is_float {
+        // Hexadecimal floating-point literals are not supported.
+        if is_hex {
+            return None;
+        }
+
+        let (digits, single) = if let Some(digits) = lower.strip_suffix('f') {
+            (digits, true)
+        } else {
+            (lower.strip_suffix('l').unwrap_or(&lower), false)
+        };
+
+        return Some(MacroValue::Float {
+            value: digits.parse().ok()?,
+            single,
+        });
+    }
+
+    let digits = lower.trim_end_matches(['u', 'l']);
+    let suffix = &lower[digits.len()..];
+    let unsigned = suffix.contains('u');
+    let long_long = suffix.contains("ll");
+
+    let (digits, radix) = if let Some(digits) = digits.strip_prefix("0x") {
+        (digits, 16)
+    } else if let Some(digits) = digits.strip_prefix("0b") {
+        (digits, 2)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        (&digits[1..], 8)
+    } else {
+        (digits, 10)
+    };
+    let value = u64::from_str_radix(digits, radix).ok()? as i128;
+
+    // Pick the first type which can represent the value, following the rules for integer
+    // literals (where `long` is treated as `int` or `long long` depending on the value).
+    let candidates: &[(bool, bool)] = match (unsigned, radix == 10) {
+        (true, _) => &[(true, false), (true, true)],
+        (false, true) => &[(false, false), (false, true)],
+        (false, false) => &[(false, false), (true, false), (false, true), (true, true)],
+    };
+    candidates
+        .iter()
+        .filter(|(_, wide)| *wide || !long_long)
+        .find(|(unsigned, wide)| wrap(value, *unsigned, *wide) == value)
+        .map(|&(unsigned, wide)| MacroValue::int(value, unsigned, wide))
+}
+
+fn parse_char(token: &str) -> Option<MacroValue> {
+    let contents = token.strip_prefix('\'')?.strip_suffix('\'')?;
+    let unescaped = unescape(contents)?;
+
+    let mut chars = unescaped.chars();
+    let c = chars.next()?;
+    if chars.next().is_some() || !c.is_ascii() {
+        return None;
+    }
+
+    Some(MacroValue::int(c as i128, false, false))
+}
+
+fn parse_string(token: &str) -> Option<String> {
+    let contents = token.strip_prefix('"')?.strip_suffix('"')?;
+    unescape(contents)
+}
+
+/// Processes escape sequences in the contents of a character or string literal. Returns `None`
+/// for escape sequences which don't correspond to an ASCII character.
+fn unescape(contents: &str) -> Option<String> {
+    let mut result = String::new();
+
+    let mut chars = contents.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            'a' => '\x07',
+            'b' => '\x08',
+            'f' => '\x0C',
+            'v' => '\x0B',
+            c @ ('\\' | '\'' | '"' | '?') => c,
+            'x' => {
+                let mut value = 0u32;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(16)) {
+                    value = value.checked_mul(16)? + digit;
+                    chars.next();
+                }
+                char::from_u32(value).filter(char::is_ascii)?
+            }
+            c @ '0'..='7' => {
+                let mut value = c.to_digit(8)?;
+                for _ in 0..2 {
+                    let Some(digit) = chars.peek().and_then(|c| c.to_digit(8)) else {
+                        break;
+                    };
+                    value = value * 8 + digit;
+                    chars.next();
+                }
+                char::from_u32(value).filter(char::is_ascii)?
+            }
+            _ => return None,
+        };
+        result.push(escaped);
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Evaluator, MacroValue};
+
+    fn evaluate(definitions: &[(&str, &[&str])], name: &str) -> Option<MacroValue> {
+        let definitions = definitions
+            .iter()
+            .map(|(name, tokens)| {
+                let tokens = tokens.iter().map(|token| token.to_string()).collect();
+                (name.to_string(), tokens)
+            })
+            .collect::<HashMap<_, _>>();
+
+        Evaluator::new(&definitions).evaluate(name)
+    }
+
+    fn int(value: i128, unsigned: bool, wide: bool) -> Option<MacroValue> {
+        Some(MacroValue::Int {
+            value,
+            unsigned,
+            wide,
+        })
+    }
+
+    #[test]
+    fn integer_literals() {
+        assert_eq!(evaluate(&[("A", &["42"])], "A"), int(42, false, false));
+        assert_eq!(evaluate(&[("A", &["0x10u"])], "A"), int(16, true, false));
+        assert_eq!(evaluate(&[("A", &["010"])], "A"), int(8, false, false));
+        assert_eq!(
+            evaluate(&[("A", &["0xFFFFFFFF"])], "A"),
+            int(0xFFFFFFFF, true, false)
+        );
+        assert_eq!(
+            evaluate(&[("A", &["4294967296"])], "A"),
+            int(4294967296, false, true)
+        );
+        assert_eq!(evaluate(&[("A", &["1LL"])], "A"), int(1, false, true));
+        assert_eq!(evaluate(&[("A", &["'a'"])], "A"), int(97, false, false));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let definitions: &[(&str, &[&str])] = &[
+            ("MAJOR", &["3"]),
+            ("MINOR", &["7"]),
+            ("VERSION", &["(", "MAJOR", "<<", "16", ")", "|", "MINOR"]),
+            ("NEGATIVE", &["-", "1"]),
+            ("MIXED", &["NEGATIVE", "+", "0u"]),
+            ("PRECEDENCE", &["1", "+", "2", "*", "3", "-", "4", "/", "2"]),
+            ("COMPARE", &["MAJOR", ">", "2", "&&", "!", "0"]),
+        ];
+
+        assert_eq!(evaluate(definitions, "VERSION"), int(0x30007, false, false));
+        assert_eq!(evaluate(definitions, "NEGATIVE"), int(-1, false, false));
+        assert_eq!(evaluate(definitions, "MIXED"), int(0xFFFFFFFF, true, false));
+        assert_eq!(evaluate(definitions, "PRECEDENCE"), int(5, false, false));
+        assert_eq!(evaluate(definitions, "COMPARE"), int(1, false, false));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(
+            evaluate(&[("A", &["1.5f"])], "A"),
+            Some(MacroValue::Float {
+                value: 1.5,
+                single: true
+            })
+        );
+        assert_eq!(
+            evaluate(&[("A", &["1e3", "/", "4"])], "A"),
+            Some(MacroValue::Float {
+                value: 250.0,
+                single: false
+            })
+        );
+    }
+
+    #[test]
+    fn strings() {
+        let definitions: &[(&str, &[&str])] = &[
+            ("NAME", &["\"VST\""]),
+            ("FULL_NAME", &["NAME", "\" 3\\n\""]),
+        ];
+
+        assert_eq!(
+            evaluate(definitions, "FULL_NAME"),
+            Some(MacroValue::Str(String::from("VST 3\n")))
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        let definitions: &[(&str, &[&str])] = &[
+            ("EMPTY", &[]),
+            ("CAST", &["(", "int", ")", "1"]),
+            ("RECURSIVE", &["RECURSIVE", "+", "1"]),
+            ("DIV_ZERO", &["1", "/", "0"]),
+            ("KEYWORD", &["struct"]),
+            ("TRAILING", &["1", "2"]),
+        ];
+
+        for (name, _) in definitions {
+            assert_eq!(evaluate(definitions, name), None, "{name}");
+        }
+    }
+}
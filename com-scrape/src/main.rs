@@ -0,0 +1,289 @@
+//! Command-line interface for `com-scrape`, for generating bindings outside of a build script.
+//!
+//! ```console
+//! com-scrape [OPTIONS] [HEADER | DIR]...
+//! ```
+//!
+//! Each input is either a header file or a directory, which is searched recursively for headers.
+//! The options mirror the methods of [`com_scrape::Generator`], and can also be given in a config
+//! file passed via `--config` (see [`com_scrape::Config`] for the format). Options given on the
+//! command line are applied after those from the config file: lists are extended, and single
+//! values override the config file.
+
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::{env, process};
+
+use com_scrape::{
+    Config, DiagnosticPolicy, Generator, IidMacro, IidStyle, OverloadNaming, Severity,
+};
+
+/// File extensions recognized as headers when searching a directory.
+const HEADER_EXTENSIONS: &[&str] = &["h", "hh", "hpp", "hxx"];
+
+const USAGE: &str = "\
+usage: com-scrape [OPTIONS] [HEADER | DIR]...
+
+options:
+    -c, --config <FILE>             read options from a TOML or JSON file
+    -o, --output <FILE>             write bindings to FILE instead of stdout
+    -I, --include-path <DIR>        add DIR to the include paths passed to libclang
+    -D, --define <NAME>[=VALUE]     define the preprocessor macro NAME (as 1 if no value is
+                                    given)
+        --std <STD>                 parse using the given C++ standard, e.g. `c++17`
+        --clang-arg <ARG>           pass ARG to libclang
+        --target <TRIPLE>           generate bindings for the given rustc target
+        --cfg-target <TRIPLE>       generate bindings for each given target in a single file,
+                                    using `#[cfg]` for definitions which differ
+        --skip-type <NAME>          do not generate bindings for NAME
+        --skip-interface-trait <NAME>
+                                    do not generate an interface trait for NAME
+        --opaque-type <NAME>        generate an opaque type for NAME
+        --map-type <CXX>=<RUST>     use the Rust type RUST in place of the C++ type CXX
+        --allow-item <REGEX>        only generate matching items and their dependencies
+        --block-item <REGEX>        do not generate matching items
+        --allow-header <GLOB>       only generate items from matching headers and their
+                                    dependencies
+        --block-header <GLOB>       do not generate items from matching headers
+        --skip-header <GLOB>        exclude matching headers from the inputs
+        --iid-macro <NAME>:<STYLE>  recognize IIDs declared by the macro NAME, with one of the
+                                    styles `bytes`, `guid`, `words`, `platform-words`
+        --iid-expr <EXPR>           Rust expression for the IID of the interface `{name}`
+        --iid-constant-type <TYPE>  generate an `{name}_iid` constant of type TYPE for each
+                                    interface with a macro-declared IID
+        --query-interface-fn <PATH> function called by `Unknown::query_interface`
+        --add-ref-fn <PATH>         function called by `Unknown::add_ref`
+        --release-fn <PATH>         function called by `Unknown::release`
+        --overload-naming <SCHEME>  name overloads by `index` or `argument-types`
+        --diagnostic-policy <POLICY>
+                                    one of `ignore`, `fail-on-error`, `fail-on-warning`
+        --snake-case-methods        use snake_case names for interface trait methods
+        --layout-tests              generate layout assertions
+        --fixed-width-integers      generate `<stdint.h>` typedefs, `char16_t`, and 16-bit
+                                    `wchar_t` as Rust fixed-width integer types
+        --macro-constants           generate constants for object-like macros
+        --allow-macro <NAME>        only generate constants for the allowed macros
+        --block-macro <NAME>        do not generate a constant for NAME
+    -h, --help                      print this message
+";
+
+struct Args {
+    config_path: Option<PathBuf>,
+    output: Option<PathBuf>,
+    inputs: Vec<PathBuf>,
+    config: Config,
+}
+
+fn usage() -> ! {
+    eprint!("{USAGE}");
+    process::exit(2);
+}
+
+fn parse_args() -> Args {
+    let mut config_path = None;
+    let mut output = None;
+    let mut inputs = Vec::new();
+    let mut config = Config::default();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next().unwrap_or_else(|| {
+                eprintln!("missing value for `{arg}`");
+                usage();
+            })
+        };
+
+        match arg.as_str() {
+            "-c" | "--config" => config_path = Some(PathBuf::from(value())),
+            "-o" | "--output" => output = Some(PathBuf::from(value())),
+            "-I" | "--include-path" => config.include_paths.push(PathBuf::from(value())),
+            "-D" | "--define" => {
+                let value = value();
+                let (name, value) = value.split_once('=').unwrap_or((&value, "1"));
+                config.defines.insert(name.to_string(), value.to_string());
+            }
+            "--std" => config.std = Some(value()),
+            "--clang-arg" => config.clang_args.push(value()),
+            "--target" => config.target = Some(value()),
+            "--cfg-target" => config.cfg_targets.push(value()),
+            "--skip-type" => config.skip_types.push(value()),
+            "--skip-interface-trait" => config.skip_interface_traits.push(value()),
+            "--opaque-type" => config.opaque_types.push(value()),
+            "--map-type" => {
+                let value = value();
+                let Some((type_, rust_type)) = value.split_once('=') else {
+                    eprintln!("expected `<CXX>=<RUST>` for `--map-type`");
+                    usage();
+                };
+                config
+                    .map_types
+                    .insert(type_.to_string(), rust_type.to_string());
+            }
+            "--allow-item" => config.allow_items.push(value()),
+            "--block-item" => config.block_items.push(value()),
+            "--allow-header" => config.allow_headers.push(value()),
+            "--block-header" => config.block_headers.push(value()),
+            "--skip-header" => config.skip_headers.push(value()),
+            "--iid-macro" => {
+                let value = value();
+                let Some((name, style)) = value.split_once(':') else {
+                    eprintln!("expected `<NAME>:<STYLE>` for `--iid-macro`");
+                    usage();
+                };
+                let style = match style {
+                    "bytes" => IidStyle::Bytes,
+                    "guid" => IidStyle::Guid,
+                    "words" => IidStyle::Words,
+                    "platform-words" => IidStyle::PlatformWords,
+                    other => {
+                        eprintln!("unknown IID style `{other}`");
+                        usage();
+                    }
+                };
+                config.iid_macros.push(IidMacro::new(name, style));
+            }
+            "--iid-expr" => config.iid_expr = Some(value()),
+            "--iid-constant-type" => config.iid_constant_type = Some(value()),
+            "--query-interface-fn" => config.query_interface_fn = Some(value()),
+            "--add-ref-fn" => config.add_ref_fn = Some(value()),
+            "--release-fn" => config.release_fn = Some(value()),
+            "--overload-naming" => {
+                let naming = match value().as_str() {
+                    "index" => OverloadNaming::Index,
+                    "argument-types" => OverloadNaming::ArgumentTypes,
+                    other => {
+                        eprintln!("unknown overload naming scheme `{other}`");
+                        usage();
+                    }
+                };
+                config.overload_naming = Some(naming);
+            }
+            "--diagnostic-policy" => {
+                let policy = match value().as_str() {
+                    "ignore" => DiagnosticPolicy::Ignore,
+                    "fail-on-error" => DiagnosticPolicy::FailOnError,
+                    "fail-on-warning" => DiagnosticPolicy::FailOnWarning,
+                    other => {
+                        eprintln!("unknown diagnostic policy `{other}`");
+                        usage();
+                    }
+                };
+                config.diagnostic_policy = Some(policy);
+            }
+            "--snake-case-methods" => config.snake_case_methods = Some(true),
+            "--layout-tests" => config.layout_tests = Some(true),
+            "--fixed-width-integers" => config.fixed_width_integers = Some(true),
+            "--macro-constants" => config.macro_constants = Some(true),
+            "--allow-macro" => config.allow_macros.push(value()),
+            "--block-macro" => config.block_macros.push(value()),
+            "-h" | "--help" => {
+                print!("{USAGE}");
+                process::exit(0);
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("unexpected argument `{arg}`");
+                usage();
+            }
+            _ => inputs.push(PathBuf::from(arg)),
+        }
+    }
+
+    if inputs.is_empty() && config_path.is_none() {
+        eprintln!("no input headers");
+        usage();
+    }
+
+    Args {
+        config_path,
+        output,
+        inputs,
+        config,
+    }
+}
+
+fn find_headers(path: &Path, headers: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        headers.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    // Sort entries so that output doesn't depend on directory iteration order
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() {
+            find_headers(&entry, headers)?;
+        } else if let Some(ext) = entry.extension().and_then(|ext| ext.to_str()) {
+            if HEADER_EXTENSIONS.contains(&ext) {
+                headers.push(entry);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let mut generator = match &args.config_path {
+        Some(path) => Generator::from_config(path)?,
+        None => Generator::default(),
+    };
+
+    // Paths given on the command line are relative to the working directory
+    generator = generator.config(&args.config, "")?;
+
+    // `--skip-header` patterns apply to the inputs given on the command line, in the same way that
+    // `skip-headers` in a config file applies to its `headers`
+    let skip = args
+        .config
+        .skip_headers
+        .iter()
+        .map(|pattern| glob::Pattern::new(pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for path in &args.inputs {
+        let mut headers = Vec::new();
+        find_headers(path, &mut headers)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+
+        for header in headers {
+            if !skip.iter().any(|pattern| pattern.matches_path(&header)) {
+                generator = generator.header(header);
+            }
+        }
+    }
+
+    // Generate into a buffer first so that a failed run doesn't clobber an existing output file
+    let mut bindings = Vec::new();
+    let diagnostics = generator.generate("", &mut bindings)?;
+    for diagnostic in diagnostics {
+        if diagnostic.severity >= Severity::Warning {
+            eprintln!("{diagnostic}");
+        }
+    }
+
+    if let Some(output) = &args.output {
+        fs::write(output, bindings)
+            .map_err(|err| format!("failed to write {}: {err}", output.display()))?;
+    } else {
+        io::stdout().write_all(&bindings)?;
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args = parse_args();
+
+    if let Err(err) = run(&args) {
+        eprintln!("error: {err}");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::ir::{Argument, Function, Method, Type};
+
+/// Controls how overloaded virtual methods and free functions are given unique names in the
+/// generated bindings.
+///
+/// Methods and functions which aren't overloaded always keep their C++ names. The method names
+/// produced here are the ones passed to
+/// [`Generator::method_renamer`](crate::Generator::method_renamer).
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum OverloadNaming {
+    /// The first overload (in declaration order) keeps the C++ name, and each later overload is
+    /// suffixed with its index, e.g. `getInfo`, `getInfo_1`, `getInfo_2`.
+    #[default]
+    Index,
+    /// Each overload is suffixed with the names of its argument types, e.g. `setValue_int32` and
+    /// `setValue_double`. An overload without arguments keeps the C++ name.
+    ArgumentTypes,
+}
+
+/// Numbers the overloads of each virtual method, which must be in declaration order.
+pub(crate) fn number_overloads(methods: &mut [Method]) {
+    for index in 0..methods.len() {
+        let name = &methods[index].name;
+        let is_overloaded = methods.iter().filter(|method| method.name == *name).count() > 1;
+        if is_overloaded {
+            let previous = methods[..index]
+                .iter()
+                .filter(|method| method.name == *name)
+                .count();
+            methods[index].overload = Some(previous);
+        }
+    }
+}
+
+/// Reorders virtual methods from declaration order to MSVC's vtable order, in which all overloads
+/// of a method are grouped at the position of the first one, in reverse declaration order.
+pub(crate) fn msvc_vtable_order(methods: Vec<Method>) -> Vec<Method> {
+    let mut remaining = methods.into_iter().map(Some).collect::<Vec<_>>();
+
+    let mut ordered = Vec::with_capacity(remaining.len());
+    for index in 0..remaining.len() {
+        let Some(name) = remaining[index].as_ref().map(|method| method.name.clone()) else {
+            continue;
+        };
+
+        let group_start = ordered.len();
+        for slot in &mut remaining[index..] {
+            if slot.as_ref().is_some_and(|method| method.name == name) {
+                ordered.push(slot.take().unwrap());
+            }
+        }
+        ordered[group_start..].reverse();
+    }
+
+    ordered
+}
+
+/// A virtual method or free function, which may be one of several overloads with the same name.
+pub(crate) trait Overload {
+    fn name(&self) -> &str;
+    fn arguments(&self) -> &[Argument];
+    fn overload(&self) -> Option<usize>;
+}
+
+impl Overload for Method {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn arguments(&self) -> &[Argument] {
+        &self.arguments
+    }
+
+    fn overload(&self) -> Option<usize> {
+        self.overload
+    }
+}
+
+impl Overload for Function {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn arguments(&self) -> &[Argument] {
+        &self.arguments
+    }
+
+    fn overload(&self) -> Option<usize> {
+        self.overload
+    }
+}
+
+/// Returns a unique name for each of `items`.
+pub(crate) fn unique_names<T: Overload>(items: &[T], naming: OverloadNaming) -> Vec<String> {
+    let mut taken = items
+        .iter()
+        .filter(|item| item.overload().is_none())
+        .map(|item| item.name().to_string())
+        .collect::<HashSet<_>>();
+
+    let mut names = Vec::with_capacity(items.len());
+    for item in items {
+        let Some(overload) = item.overload() else {
+            names.push(item.name().to_string());
+            continue;
+        };
+
+        let mut name = match naming {
+            OverloadNaming::Index if overload == 0 => item.name().to_string(),
+            OverloadNaming::Index => format!("{}_{overload}", item.name()),
+            OverloadNaming::ArgumentTypes => {
+                let mut name = item.name().to_string();
+                for argument in item.arguments() {
+                    name.push('_');
+                    name.push_str(&type_suffix(&argument.type_));
+                }
+                name
+            }
+        };
+
+        // Fall back to the overload index if the name is still ambiguous (e.g. for overloads
+        // which only differ in constness).
+        if taken.contains(&name) {
+            name = format!("{name}_{overload}");
+        }
+        while taken.contains(&name) {
+            name.push('_');
+        }
+
+        taken.insert(name.clone());
+        names.push(name);
+    }
+
+    names
+}
+
+fn type_suffix(type_: &Type) -> String {
+    match type_ {
+        Type::Void => String::from("void"),
+        Type::Bool => String::from("bool"),
+        Type::Char => String::from("char"),
+        Type::UChar => String::from("uchar"),
+        Type::UShort => String::from("ushort"),
+        Type::UInt => String::from("uint"),
+        Type::ULong => String::from("ulong"),
+        Type::ULongLong => String::from("ulonglong"),
+        Type::SChar => String::from("schar"),
+        Type::Short => String::from("short"),
+        Type::Int => String::from("int"),
+        Type::Long => String::from("long"),
+        Type::LongLong => String::from("longlong"),
+        Type::Unsigned(size) => format!("u{}", size * 8),
+        Type::Signed(size) => format!("i{}", size * 8),
+        Type::Float => String::from("float"),
+        Type::Double => String::from("double"),
+        Type::Pointer { pointee, .. } | Type::Reference { pointee, .. } => {
+            format!("{}_ptr", type_suffix(pointee))
+        }
+        Type::Record { name, .. } | Type::Typedef { name, .. } => name.clone(),
+        Type::UnnamedRecord(_) => String::from("record"),
+        Type::Array(_, element) => format!("{}_array", type_suffix(element)),
+        Type::Function { .. } => String::from("fn"),
+        Type::Mapped { name, .. } => name.rsplit("::").next().unwrap_or(name).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn method(name: &str, arguments: &[Type]) -> Method {
+        Method {
+            arguments: arguments
+                .iter()
+                .map(|type_| Argument {
+                    name: String::new(),
+                    type_: type_.clone(),
+                })
+                .collect(),
+            ..test_util::method(name)
+        }
+    }
+
+    fn methods() -> Vec<Method> {
+        let mut methods = vec![
+            method(
+                "setValue",
+                &[Type::Typedef {
+                    name: String::from("int32"),
+                    namespace: Vec::new(),
+                }],
+            ),
+            method("getValue", &[]),
+            method("setValue", &[Type::Double]),
+            method("setValue", &[Type::Double, Type::Bool]),
+            method("reset", &[]),
+        ];
+        number_overloads(&mut methods);
+        methods
+    }
+
+    #[test]
+    fn msvc_order() {
+        let ordered = msvc_vtable_order(methods());
+        let order = ordered
+            .iter()
+            .map(|method| (method.name.as_str(), method.overload))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            order,
+            [
+                ("setValue", Some(2)),
+                ("setValue", Some(1)),
+                ("setValue", Some(0)),
+                ("getValue", None),
+                ("reset", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unique_method_names() {
+        let methods = methods();
+
+        assert_eq!(
+            unique_names(&methods, OverloadNaming::Index),
+            ["setValue", "getValue", "setValue_1", "setValue_2", "reset"]
+        );
+        assert_eq!(
+            unique_names(&methods, OverloadNaming::ArgumentTypes),
+            [
+                "setValue_int32",
+                "getValue",
+                "setValue_double",
+                "setValue_double_bool",
+                "reset"
+            ]
+        );
+
+        // Names don't depend on the vtable order
+        assert_eq!(
+            unique_names(&msvc_vtable_order(methods), OverloadNaming::Index),
+            ["setValue_2", "setValue_1", "setValue", "getValue", "reset"]
+        );
+    }
+}
@@ -1,166 +1,407 @@
-use std::collections::BTreeMap;
-use std::error::Error;
+use std::collections::{HashMap, HashSet};
+use std::mem;
 
 use crate::clang::{self, *};
+use crate::doc;
+use crate::error::Error;
+use crate::filter::{self, Filter};
+use crate::ir::{
+    Abi, Argument, Base, Bitfield, CallingConvention, Constant, Field, Function, IndirectResult,
+    Method, Namespace, OpaqueRecord, Record, RecordKind, Type, Typedef, Value, VirtualDestructor,
+};
+use crate::macros::{Evaluator, MacroValue};
+use crate::overload;
 use crate::Generator;
 
-#[derive(Clone, Debug)]
-pub struct Namespace {
-    pub children: BTreeMap<String, Namespace>,
-    pub typedefs: Vec<Typedef>,
-    pub records: Vec<Record>,
-    pub constants: Vec<Constant>,
-    pub unparsed_constants: Vec<String>,
-}
-
 impl Namespace {
-    pub fn new() -> Namespace {
-        Namespace {
-            children: BTreeMap::new(),
-            typedefs: Vec::new(),
-            records: Vec::new(),
-            constants: Vec::new(),
-            unparsed_constants: Vec::new(),
-        }
-    }
-
-    pub fn parse(cursor: &Cursor, options: &Generator) -> Result<Namespace, Box<dyn Error>> {
-        let mut parser = Parser::new(options);
+    pub(crate) fn parse(
+        cursor: &Cursor,
+        options: &Generator,
+        target: &str,
+    ) -> Result<Namespace, Error> {
+        let mut parser = Parser::new(options, target)?;
         let mut namespace = Namespace::new();
 
         cursor.visit_children(|cursor| parser.visit(&mut namespace, cursor))?;
+        parser.add_macro_constants(&mut namespace);
+        parser.assign_iids(&mut namespace, "");
+
+        filter::retain_referenced_opaque_records(
+            &mut namespace,
+            &parser.dependencies,
+            &options.opaque_types,
+        );
+
+        if parser.filter.has_allowlist() {
+            parser.filter.retain_allowed(
+                &mut namespace,
+                &parser.header_roots,
+                &parser.dependencies,
+            );
+        }
 
         Ok(namespace)
     }
+}
+
+fn malformed(location: &Location, message: &str) -> Error {
+    Error::Malformed {
+        location: location.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Returns the qualified name of a declaration (e.g. `Steinberg::Vst::IComponent`), including
+/// enclosing namespaces and records.
+fn qualified_name(decl: &Cursor) -> Result<String, Error> {
+    let mut path = vec![decl.name().to_str()?.to_string()];
 
-    pub fn is_empty(&self) -> bool {
-        self.typedefs.is_empty()
-            && self.records.is_empty()
-            && self.constants.is_empty()
-            && self.unparsed_constants.is_empty()
-            && self.children.values().all(|child| child.is_empty())
+    let mut parent = decl.semantic_parent();
+    while let Some(cursor) = parent {
+        match cursor.kind() {
+            CursorKind::Namespace
+            | CursorKind::StructDecl
+            | CursorKind::UnionDecl
+            | CursorKind::ClassDecl => path.push(cursor.name().to_str()?.to_string()),
+            CursorKind::LinkageSpec => {}
+            _ => break,
+        }
+        parent = cursor.semantic_parent();
     }
+
+    path.reverse();
+    Ok(path.join("::"))
 }
 
-#[derive(Clone, Debug)]
-pub struct Typedef {
-    pub name: String,
-    pub type_: Type,
-    pub inner: Namespace,
+/// Returns the qualified name of the item which the filter keeps or removes along with the
+/// declaration `decl`: the declaration itself, or the outermost record enclosing it.
+fn item_path(decl: &Cursor) -> Result<String, Error> {
+    let mut path = vec![decl.name().to_str()?.to_string()];
+
+    let mut parent = decl.semantic_parent();
+    while let Some(cursor) = parent {
+        match cursor.kind() {
+            CursorKind::Namespace => path.push(cursor.name().to_str()?.to_string()),
+            CursorKind::StructDecl | CursorKind::UnionDecl | CursorKind::ClassDecl => {
+                path = vec![cursor.name().to_str()?.to_string()];
+            }
+            CursorKind::LinkageSpec => {}
+            _ => break,
+        }
+        parent = cursor.semantic_parent();
+    }
+
+    path.reverse();
+    Ok(path.join("::"))
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-pub enum RecordKind {
-    Struct,
-    Union,
+/// Returns the names of the namespaces enclosing the declaration `decl`, outermost first.
+fn namespace_path(decl: &Cursor) -> Result<Vec<String>, Error> {
+    let mut path = Vec::new();
+
+    let mut parent = decl.semantic_parent();
+    while let Some(cursor) = parent {
+        match cursor.kind() {
+            CursorKind::Namespace => path.push(cursor.name().to_str()?.to_string()),
+            CursorKind::StructDecl
+            | CursorKind::UnionDecl
+            | CursorKind::ClassDecl
+            | CursorKind::LinkageSpec => {}
+            _ => break,
+        }
+        parent = cursor.semantic_parent();
+    }
+
+    path.reverse();
+    Ok(path)
 }
 
-#[derive(Clone, Debug)]
-pub struct Record {
-    pub name: String,
-    pub kind: RecordKind,
-    pub fields: Vec<Field>,
-    pub bases: Vec<Base>,
-    pub virtual_methods: Vec<Method>,
-    pub inner: Namespace,
+/// Returns the calling convention corresponding to a `libclang` calling convention, or `None` if
+/// stable Rust doesn't support it.
+fn calling_convention(calling_conv: CallingConv) -> Option<CallingConvention> {
+    match calling_conv {
+        CallingConv::C => Some(CallingConvention::C),
+        CallingConv::X86StdCall => Some(CallingConvention::Stdcall),
+        CallingConv::X86FastCall => Some(CallingConvention::Fastcall),
+        CallingConv::X86ThisCall => Some(CallingConvention::Thiscall),
+        CallingConv::Win64 => Some(CallingConvention::Win64),
+        CallingConv::X86_64SysV => Some(CallingConvention::SysV64),
+        CallingConv::X86VectorCall | CallingConv::Other => None,
+    }
 }
 
-#[derive(Clone, Debug)]
-pub struct Base {
-    pub name: String,
-    pub bases: Vec<Base>,
+/// Returns the fixed-width integer type corresponding to a `<stdint.h>` typedef such as `int32_t`,
+/// or to a UTF-16 character type.
+fn fixed_width_integer(type_: &clang::Type, location: &Location) -> Result<Option<Type>, Error> {
+    match type_.kind() {
+        TypeKind::Typedef => {
+            let Some(name) = type_.typedef_name() else {
+                return Ok(None);
+            };
+            let signed = match name.to_str()? {
+                "int8_t" | "int16_t" | "int32_t" | "int64_t" => true,
+                "uint8_t" | "uint16_t" | "uint32_t" | "uint64_t" => false,
+                _ => return Ok(None),
+            };
+
+            let integer = match (signed, type_.size()) {
+                (true, size @ (1 | 2 | 4 | 8)) => Type::Signed(size),
+                (false, size @ (1 | 2 | 4 | 8)) => Type::Unsigned(size),
+                (_, size) => {
+                    return Err(Error::UnsupportedType {
+                        location: location.to_string(),
+                        kind: format!("{} of size {size}", name.to_string_lossy()),
+                    })
+                }
+            };
+
+            Ok(Some(integer))
+        }
+        kind => Ok(utf16_char(kind, type_.size())),
+    }
 }
 
-#[derive(Clone, Debug)]
-pub struct Field {
-    pub name: Option<String>,
-    pub type_: Type,
+/// Returns `u16` for `char16_t`, and for `wchar_t` where it is 16 bits wide (as on Windows, where
+/// UTF-16 character types such as `Steinberg::char16` are defined as `wchar_t`).
+fn utf16_char(kind: TypeKind, size: usize) -> Option<Type> {
+    match (kind, size) {
+        (TypeKind::Char16, _) | (TypeKind::WChar, 2) => Some(Type::Unsigned(2)),
+        _ => None,
+    }
 }
 
-#[derive(Clone, Debug)]
-pub struct Method {
-    pub name: String,
-    pub arguments: Vec<Argument>,
-    pub result_type: Type,
+/// Returns the USR of the record declaration for `type_`, or for its element type if it is an
+/// array, or `None` if it isn't a record.
+fn record_usr(type_: &clang::Type) -> Result<Option<String>, Error> {
+    let mut type_ = type_.canonical_type();
+    while let Some(element_type) = type_.array_element_type() {
+        type_ = element_type.canonical_type();
+    }
+
+    if type_.kind() == TypeKind::Record {
+        Ok(Some(type_.declaration().usr().to_str()?.to_string()))
+    } else {
+        Ok(None)
+    }
 }
 
-#[derive(Clone, Debug)]
-pub struct Argument {
-    pub name: String,
-    pub type_: Type,
+/// Returns `true` if `type_` is trivial for the purposes of calls under the Itanium ABI, i.e. if it
+/// is passed and returned in registers when small enough rather than always through a hidden
+/// pointer. Records with user-provided or deleted copy constructors, move constructors, or
+/// destructors, or with virtual methods or bases, are not.
+fn is_trivial_for_calls(type_: &clang::Type) -> Result<bool, Error> {
+    let mut type_ = type_.canonical_type();
+    while let Some(element_type) = type_.array_element_type() {
+        type_ = element_type.canonical_type();
+    }
+
+    if type_.kind() != TypeKind::Record || type_.is_pod() {
+        return Ok(true);
+    }
+
+    let mut trivial = true;
+    type_
+        .declaration()
+        .visit_children(|cursor| -> Result<(), Error> {
+            match cursor.kind() {
+                CursorKind::CxxConstructor
+                    if (cursor.is_copy_constructor() || cursor.is_move_constructor())
+                        && !cursor.is_defaulted() =>
+                {
+                    trivial = false;
+                }
+                CursorKind::CxxDestructor if !cursor.is_defaulted() || cursor.is_virtual() => {
+                    trivial = false;
+                }
+                CursorKind::CxxMethod if cursor.is_virtual() => trivial = false,
+                CursorKind::CxxBaseSpecifier | CursorKind::FieldDecl => {
+                    if cursor.kind() == CursorKind::CxxBaseSpecifier && cursor.is_virtual_base() {
+                        trivial = false;
+                    }
+
+                    let type_ = cursor
+                        .type_()
+                        .ok_or_else(|| malformed(&cursor.location(), "member has no type"))?;
+                    trivial &= is_trivial_for_calls(&type_)?;
+                }
+                _ => {}
+            }
+
+            Ok(())
+        })?;
+
+    Ok(trivial)
 }
 
-#[derive(Clone, Debug)]
-pub struct Constant {
-    pub name: String,
-    pub type_: Type,
-    pub value: Value,
+/// Returns `true` if any base class of the record declared at `decl` (transitively) declares a
+/// virtual destructor.
+fn inherits_virtual_destructor(decl: &Cursor) -> Result<bool, Error> {
+    let mut found = false;
+    decl.visit_children(|cursor| -> Result<(), Error> {
+        if cursor.kind() == CursorKind::CxxBaseSpecifier {
+            let base_type = cursor
+                .type_()
+                .ok_or_else(|| malformed(&cursor.location(), "base class has no type"))?;
+            let base = base_type.declaration();
+
+            base.visit_children(|cursor| -> Result<(), Error> {
+                if cursor.kind() == CursorKind::CxxDestructor && cursor.is_virtual() {
+                    found = true;
+                }
+                Ok(())
+            })?;
+            found |= inherits_virtual_destructor(&base)?;
+        }
+
+        Ok(())
+    })?;
+
+    Ok(found)
 }
 
-#[derive(Clone, Debug)]
-pub enum Type {
-    Void,
-    Bool,
-    Char,
-    UChar,
-    UShort,
-    UInt,
-    ULong,
-    ULongLong,
-    SChar,
-    Short,
-    Int,
-    Long,
-    LongLong,
-    Unsigned(usize),
-    #[allow(unused)]
-    Signed(usize),
-    Float,
-    Double,
-    Pointer {
-        is_const: bool,
-        pointee: Box<Type>,
-    },
-    Reference {
-        is_const: bool,
-        pointee: Box<Type>,
-    },
-    Record(String),
-    UnnamedRecord(Record),
-    Typedef(String),
-    Array(usize, Box<Type>),
+fn parse_doc(cursor: &Cursor) -> Option<String> {
+    let comment = cursor.raw_comment()?;
+    doc::convert_comment(&comment.to_string_lossy())
 }
 
-#[derive(Clone, Debug)]
-pub enum Value {
-    Signed(i64),
-    Unsigned(u64),
-    Float(f64),
-    Str(String),
+/// Splits the tokens of a function-like macro invocation following the macro name into its
+/// arguments. Returns `None` if the tokens do not start with a complete argument list.
+fn split_macro_args(tokens: &[String]) -> Option<Vec<&[String]>> {
+    if tokens.first()? != "(" {
+        return None;
+    }
+
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut start = 1;
+    for (i, token) in tokens.iter().enumerate().skip(1) {
+        match token.as_str() {
+            "(" => depth += 1,
+            ")" if depth == 0 => {
+                args.push(&tokens[start..i]);
+                return Some(args);
+            }
+            ")" => depth -= 1,
+            "," if depth == 0 => {
+                args.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    None
 }
 
 struct Parser<'a> {
     options: &'a Generator,
+    filter: Filter,
+    /// The names of the namespaces enclosing the cursor currently being visited.
+    path: Vec<String>,
+    /// Qualified names of the items declared in allowed headers.
+    header_roots: HashSet<String>,
+    /// Qualified names of the items which each item refers to, by qualified name.
+    dependencies: HashMap<String, HashSet<String>>,
+    /// Qualified names of the items referred to by the item currently being parsed.
+    current_dependencies: HashSet<String>,
+    /// Names of object-like macros in order of definition.
+    macro_names: Vec<String>,
+    /// Tokens of the most recent definition of each object-like macro.
+    macro_tokens: HashMap<String, Vec<String>>,
+    /// IIDs declared via IID macros, by the qualified name of the interface (relative to the
+    /// namespace in which the macro was invoked).
+    iids: HashMap<String, [u8; 16]>,
+    /// USRs of the records which are emitted with a `repr(align)` attribute or contain such a
+    /// record by value, and therefore can't be placed in a `repr(packed)` record.
+    aligned_records: HashSet<String>,
+    /// Whether the target uses the MSVC C++ ABI, which orders overloaded virtual methods
+    /// differently.
+    msvc: bool,
+    /// Whether records can be returned through a hidden pointer preceding `this` under the
+    /// Itanium ABI.
+    itanium_indirect_result: bool,
+    /// Whether the target is a Windows target, which affects the byte order of some IIDs.
+    windows: bool,
 }
 
 impl<'a> Parser<'a> {
-    fn new(options: &'a Generator) -> Parser<'a> {
-        Parser { options }
+    fn new(options: &'a Generator, target: &str) -> Result<Parser<'a>, Error> {
+        let arch = target.split('-').next().unwrap_or(target);
+
+        Ok(Parser {
+            options,
+            filter: Filter::new(options)?,
+            path: Vec::new(),
+            header_roots: HashSet::new(),
+            dependencies: HashMap::new(),
+            current_dependencies: HashSet::new(),
+            macro_names: Vec::new(),
+            macro_tokens: HashMap::new(),
+            iids: HashMap::new(),
+            aligned_records: HashSet::new(),
+            msvc: target.contains("-windows-msvc"),
+            // On other targets, the hidden result pointer is either passed in a dedicated register
+            // (AArch64) or popped by the callee (32-bit x86), neither of which can be expressed
+            // as an ordinary argument.
+            itanium_indirect_result: arch == "x86_64"
+                || (arch.starts_with("arm") && !arch.starts_with("arm64"))
+                || arch.starts_with("thumb"),
+            windows: target.contains("windows"),
+        })
     }
 
-    fn visit(&mut self, namespace: &mut Namespace, cursor: &Cursor) -> Result<(), Box<dyn Error>> {
+    fn visit(&mut self, namespace: &mut Namespace, cursor: &Cursor) -> Result<(), Error> {
         if cursor.is_in_system_header() {
             return Ok(());
         }
 
-        if self
-            .options
-            .skip_types
-            .contains(cursor.name().to_str().unwrap())
-        {
+        let name = cursor.name();
+        let name = name.to_str()?;
+        if self.options.skip_types.contains(name) {
+            return Ok(());
+        }
+
+        let mut path = self.path.join("::");
+        if !path.is_empty() {
+            path.push_str("::");
+        }
+        path.push_str(name);
+
+        let file = cursor.location().file_location().file_name();
+        let file = file.as_ref().map(|file| file.to_str()).transpose()?;
+
+        // Blocked macros are still recorded, since other macros may refer to them
+        let blocked = self.filter.is_blocked(&path, file);
+        if blocked && cursor.kind() != CursorKind::MacroDefinition {
             return Ok(());
         }
 
+        if self.filter.is_header_allowed(file) {
+            self.header_roots.insert(path);
+        }
+
+        // Items nested in a record are kept or removed along with it, so their dependencies are
+        // attributed to the outermost enclosing record.
+        let enclosing_dependencies = mem::take(&mut self.current_dependencies);
+        let result = self.visit_declaration(namespace, cursor, name, blocked);
+        let dependencies = mem::replace(&mut self.current_dependencies, enclosing_dependencies);
+        if !dependencies.is_empty() {
+            self.dependencies
+                .entry(item_path(cursor)?)
+                .or_default()
+                .extend(dependencies);
+        }
+
+        result
+    }
+
+    fn visit_declaration(
+        &mut self,
+        namespace: &mut Namespace,
+        cursor: &Cursor,
+        name: &str,
+        blocked: bool,
+    ) -> Result<(), Error> {
         match cursor.kind() {
             CursorKind::Namespace => {
                 // Skip the contents of unnamed namespaces
@@ -168,35 +409,42 @@ impl<'a> Parser<'a> {
                     return Ok(());
                 }
 
-                let name = cursor.name();
-                let name_str = name.to_str().unwrap();
-
-                if !namespace.children.contains_key(name_str) {
-                    namespace
-                        .children
-                        .insert(name_str.to_string(), Namespace::new());
-                }
-                let child_namespace = namespace.children.get_mut(name_str).unwrap();
+                let child_namespace = namespace.children.entry(name.to_string()).or_default();
+                self.path.push(name.to_string());
                 cursor.visit_children(|cursor| self.visit(child_namespace, cursor))?;
+                self.path.pop();
             }
             CursorKind::TypedefDecl | CursorKind::TypeAliasDecl => {
-                let typedef = cursor.type_().unwrap();
-                let name = typedef.typedef_name();
+                let location = cursor.location();
+
+                let typedef = cursor
+                    .type_()
+                    .ok_or_else(|| malformed(&location, "typedef has no type"))?;
+                let name = typedef
+                    .typedef_name()
+                    .ok_or_else(|| malformed(&location, "typedef has no name"))?;
 
-                let type_ =
-                    self.parse_type(cursor.typedef_underlying_type().unwrap(), cursor.location())?;
+                let underlying_type = cursor
+                    .typedef_underlying_type()
+                    .ok_or_else(|| malformed(&location, "typedef has no underlying type"))?;
+                let type_ = self.parse_type(underlying_type, cursor.location())?;
 
                 namespace.typedefs.push(Typedef {
-                    name: name.unwrap().to_str().unwrap().to_string(),
+                    name: name.to_str()?.to_string(),
+                    doc: parse_doc(cursor),
                     type_,
                     inner: Namespace::new(),
                 });
             }
             CursorKind::EnumDecl => {
-                let int_type =
-                    self.parse_type(cursor.enum_integer_type().unwrap(), cursor.location())?;
+                let location = cursor.location();
+
+                let enum_integer_type = cursor
+                    .enum_integer_type()
+                    .ok_or_else(|| malformed(&location, "enum has no integer type"))?;
+                let canonical_type = enum_integer_type.canonical_type();
+                let int_type = self.parse_type(enum_integer_type, cursor.location())?;
 
-                let canonical_type = cursor.enum_integer_type().unwrap().canonical_type();
                 let signed = match canonical_type.kind() {
                     TypeKind::Char_U
                     | TypeKind::UChar
@@ -210,26 +458,32 @@ impl<'a> Parser<'a> {
                     | TypeKind::Int
                     | TypeKind::Long
                     | TypeKind::LongLong => true,
-                    _ => return Err(format!("unhandled enum type {:?}", int_type).into()),
+                    kind => {
+                        return Err(Error::UnsupportedType {
+                            location: location.to_string(),
+                            kind: format!("{kind:?}"),
+                        })
+                    }
                 };
 
                 let mut constants = Vec::new();
-                cursor.visit_children(|cursor| -> Result<(), Box<dyn Error>> {
-                    match cursor.kind() {
-                        CursorKind::EnumConstantDecl => {
-                            let value = if signed {
-                                Value::Signed(cursor.enum_constant_value().unwrap())
-                            } else {
-                                Value::Unsigned(cursor.enum_constant_value_unsigned().unwrap())
-                            };
-
-                            constants.push(Constant {
-                                name: cursor.name().to_str().unwrap().to_string(),
-                                type_: int_type.clone(),
-                                value,
-                            });
-                        }
-                        _ => {}
+                cursor.visit_children(|cursor| -> Result<(), Error> {
+                    if cursor.kind() == CursorKind::EnumConstantDecl {
+                        let value = if signed {
+                            cursor.enum_constant_value().map(Value::Signed)
+                        } else {
+                            cursor.enum_constant_value_unsigned().map(Value::Unsigned)
+                        };
+                        let value = value.ok_or_else(|| {
+                            malformed(&cursor.location(), "enum constant has no value")
+                        })?;
+
+                        constants.push(Constant {
+                            name: cursor.name().to_str()?.to_string(),
+                            doc: parse_doc(cursor),
+                            type_: int_type.clone(),
+                            value,
+                        });
                     }
 
                     Ok(())
@@ -238,21 +492,23 @@ impl<'a> Parser<'a> {
                 if cursor.is_anonymous() {
                     namespace.constants.extend(constants);
                 } else {
-                    let name = cursor.name();
-                    let name_str = name.to_str().unwrap();
-
                     let mut inner = Namespace::new();
                     inner.constants.extend(constants);
 
                     namespace.typedefs.push(Typedef {
-                        name: name_str.to_string(),
+                        name: cursor.name().to_str()?.to_string(),
+                        doc: parse_doc(cursor),
                         type_: int_type.clone(),
                         inner,
                     });
                 }
             }
             CursorKind::VarDecl => {
-                let type_ = cursor.type_().unwrap();
+                self.parse_iid(cursor)?;
+
+                let type_ = cursor
+                    .type_()
+                    .ok_or_else(|| malformed(&cursor.location(), "variable has no type"))?;
                 if type_.is_const() {
                     let eval_result = cursor.evaluate();
                     let value = match eval_result.kind() {
@@ -264,16 +520,18 @@ impl<'a> Parser<'a> {
                             }
                         }
                         EvalResultKind::Float => Some(Value::Float(eval_result.as_double())),
-                        EvalResultKind::StrLiteral => Some(Value::Str(
-                            eval_result.as_str().unwrap().to_str().unwrap().to_string(),
-                        )),
+                        EvalResultKind::StrLiteral => match eval_result.as_str() {
+                            Some(value) => Some(Value::Str(value.to_str()?.to_string())),
+                            None => None,
+                        },
                         EvalResultKind::Other => None,
                     };
 
                     if let Some(value) = value {
                         let type_ = self.parse_type(type_, cursor.location())?;
                         namespace.constants.push(Constant {
-                            name: cursor.name().to_str().unwrap().to_string(),
+                            name: cursor.name().to_str()?.to_string(),
+                            doc: parse_doc(cursor),
                             type_,
                             value,
                         });
@@ -283,8 +541,9 @@ impl<'a> Parser<'a> {
 
                             let mut token_strings = Vec::new();
                             for i in 0..tokens.len() {
-                                let token = tokens.get(i).unwrap();
-                                token_strings.push(token.spelling().to_str().unwrap().to_string());
+                                if let Some(token) = tokens.get(i) {
+                                    token_strings.push(token.spelling().to_str()?.to_string());
+                                }
                             }
 
                             if let Some(result) = parser(&token_strings) {
@@ -294,14 +553,132 @@ impl<'a> Parser<'a> {
                     }
                 }
             }
-            CursorKind::StructDecl | CursorKind::UnionDecl | CursorKind::ClassDecl => {
-                if cursor.is_definition() {
-                    // Skip unnamed records here, as parse_type will take care of them
-                    if !cursor.is_anonymous() {
-                        let record = self.parse_record(cursor.type_().unwrap())?;
-                        namespace.records.push(record);
+            CursorKind::LinkageSpec => {
+                cursor.visit_children(|cursor| self.visit(namespace, cursor))?;
+            }
+            // Inline and static functions aren't exported, and so can't be linked against
+            CursorKind::FunctionDecl if !cursor.is_function_inlined() && !cursor.is_static() => {
+                let location = cursor.location();
+
+                let name = cursor.name().to_str()?.to_string();
+
+                // Functions with C++ linkage have mangled symbol names. Apple targets prefix all
+                // symbols with an extra underscore, which rustc adds back on its own.
+                let mangling = cursor.mangling();
+                let mangling = mangling.to_str()?;
+                let link_name = if mangling.starts_with("_Z") || mangling.starts_with('?') {
+                    Some(mangling.to_string())
+                } else {
+                    mangling.strip_prefix("__Z").map(|rest| format!("_Z{rest}"))
+                };
+
+                // Redeclarations of a function are skipped. Overloads are numbered in declaration
+                // order, and given unique names when printed.
+                let mut overloads = namespace.functions.iter().filter(|f| f.name == name);
+                if overloads.any(|f| f.link_name == link_name) {
+                    return Ok(());
+                }
+
+                let type_ = cursor
+                    .type_()
+                    .ok_or_else(|| malformed(&location, "function has no type"))?;
+                if type_.is_variadic() {
+                    return Ok(());
+                }
+
+                // Like variadic functions, functions with calling conventions which stable Rust
+                // doesn't support are skipped
+                let Some(calling_convention) = calling_convention(type_.calling_conv()) else {
+                    return Ok(());
+                };
+
+                let arguments = self.parse_arguments(cursor)?;
+
+                let result_type = cursor
+                    .result_type()
+                    .ok_or_else(|| malformed(&location, "function has no result type"))?;
+                let result_type = self.parse_type(result_type, cursor.location())?;
+
+                let mut overloads = namespace.functions.iter_mut().filter(|f| f.name == name);
+                let overload = match overloads.next() {
+                    Some(first) => {
+                        first.overload.get_or_insert(0);
+                        Some(overloads.count() + 1)
+                    }
+                    None => None,
+                };
+
+                namespace.functions.push(Function {
+                    name,
+                    doc: parse_doc(cursor),
+                    link_name,
+                    calling_convention,
+                    arguments,
+                    result_type,
+                    overload,
+                });
+            }
+            // Macro definitions only appear at the top level of the translation unit, and only when
+            // macro constants are enabled.
+            CursorKind::MacroDefinition if !cursor.is_macro_function_like() => {
+                // Skip built-in and command-line macros
+                if cursor.location().file_location().file_name().is_none() {
+                    return Ok(());
+                }
+
+                let tokens = cursor.tokens();
+
+                // The first token is the name of the macro itself.
+                let mut token_strings = Vec::new();
+                for i in 1..tokens.len() {
+                    if let Some(token) = tokens.get(i) {
+                        token_strings.push(token.spelling().to_str()?.to_string());
+                    }
+                }
+
+                let name = name.to_string();
+                if self
+                    .macro_tokens
+                    .insert(name.clone(), token_strings)
+                    .is_none()
+                    && !blocked
+                {
+                    self.macro_names.push(name);
+                }
+            }
+            // Skip unnamed records here, as parse_type will take care of them
+            CursorKind::StructDecl | CursorKind::UnionDecl | CursorKind::ClassDecl
+                if !cursor.is_anonymous() =>
+            {
+                let name = cursor.name().to_str()?.to_string();
+
+                // Records which are referenced but never defined (and records which have been
+                // configured as opaque) are emitted as opaque types.
+                let opaque = if cursor.is_definition() {
+                    self.options.opaque_types.contains(&name)
+                } else {
+                    !cursor.has_definition()
+                };
+
+                if opaque {
+                    if !namespace.opaque_records.iter().any(|r| r.name == name) {
+                        namespace.opaque_records.push(OpaqueRecord {
+                            name,
+                            doc: parse_doc(cursor),
+                        });
                     }
+                    return Ok(());
                 }
+
+                if !cursor.is_definition() {
+                    return Ok(());
+                }
+
+                let type_ = cursor
+                    .type_()
+                    .ok_or_else(|| malformed(&cursor.location(), "record has no type"))?;
+                let record = self.parse_record(type_)?;
+                namespace.records.push(record);
             }
             _ => {}
         }
@@ -309,69 +686,285 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
-    fn parse_record(&mut self, record: clang::Type) -> Result<Record, Box<dyn Error>> {
+    /// Checks whether the variable declaration at `cursor` was expanded from one of the
+    /// configured IID macros, and if so, records the IID which it declares.
+    fn parse_iid(&mut self, cursor: &Cursor) -> Result<(), Error> {
+        if self.options.iid_macros.is_empty() {
+            return Ok(());
+        }
+
+        let tokens = cursor.tokens();
+
+        let mut token_strings = Vec::new();
+        for i in 0..tokens.len() {
+            if let Some(token) = tokens.get(i) {
+                token_strings.push(token.spelling().to_str()?.to_string());
+            }
+        }
+
+        let Some(first) = token_strings.first() else {
+            return Ok(());
+        };
+        let Some(iid_macro) = self.options.iid_macros.iter().find(|m| &m.name == first) else {
+            return Ok(());
+        };
+
+        let location = cursor.location();
+        let macro_name = &iid_macro.name;
+
+        let args = split_macro_args(&token_strings[1..]).ok_or_else(|| {
+            malformed(
+                &location,
+                &format!("invalid invocation of IID macro {macro_name}"),
+            )
+        })?;
+
+        let interface = match args.get(iid_macro.interface_arg) {
+            Some([name]) => name.clone(),
+            _ => {
+                let message = format!("IID macro {macro_name} has no interface name argument");
+                return Err(malformed(&location, &message));
+            }
+        };
+
+        let limits = iid_macro.style.arg_limits();
+        let value_args = args
+            .get(iid_macro.value_arg..iid_macro.value_arg + limits.len())
+            .ok_or_else(|| {
+                malformed(
+                    &location,
+                    &format!("too few arguments to IID macro {macro_name}"),
+                )
+            })?;
+
+        let mut evaluator = Evaluator::new(&self.macro_tokens);
+        let mut values = Vec::new();
+        for (arg, &limit) in value_args.iter().zip(limits) {
+            match evaluator.evaluate_tokens(arg) {
+                Some(MacroValue::Int { value, .. }) if (0..=limit as i128).contains(&value) => {
+                    values.push(value as u64);
+                }
+                _ => {
+                    let message = format!("invalid IID argument `{}`", arg.join(" "));
+                    return Err(malformed(&location, &message));
+                }
+            }
+        }
+
+        let mut path = self.path.clone();
+        path.push(interface);
+
+        let iid = iid_macro.style.to_bytes(&values, self.windows);
+        self.iids.insert(path.join("::"), iid);
+
+        Ok(())
+    }
+
+    /// Attaches the IIDs found by `parse_iid` to the corresponding records. `prefix` is the
+    /// qualified name of `namespace`, followed by `::` unless it is empty.
+    fn assign_iids(&self, namespace: &mut Namespace, prefix: &str) {
+        if self.iids.is_empty() {
+            return;
+        }
+
+        for record in &mut namespace.records {
+            if let Some(iid) = self.iids.get(&format!("{prefix}{}", record.name)) {
+                record.iid = Some(*iid);
+            }
+            self.assign_iids(&mut record.inner, prefix);
+        }
+
+        for typedef in &mut namespace.typedefs {
+            self.assign_iids(&mut typedef.inner, prefix);
+        }
+
+        for (name, child) in &mut namespace.children {
+            self.assign_iids(child, &format!("{prefix}{name}::"));
+        }
+    }
+
+    fn add_macro_constants(&self, namespace: &mut Namespace) {
+        let options = self.options;
+        let mut evaluator = Evaluator::new(&self.macro_tokens);
+
+        for name in &self.macro_names {
+            if options.block_macros.contains(name)
+                || (!options.allow_macros.is_empty() && !options.allow_macros.contains(name))
+            {
+                continue;
+            }
+
+            if namespace.constants.iter().any(|c| &c.name == name) {
+                continue;
+            }
+
+            let (type_, value) = match evaluator.evaluate(name) {
+                Some(MacroValue::Int {
+                    value,
+                    unsigned,
+                    wide,
+                }) => match (unsigned, wide) {
+                    (false, false) => (Type::Int, Value::Signed(value as i64)),
+                    (true, false) => (Type::UInt, Value::Unsigned(value as u64)),
+                    (false, true) => (Type::LongLong, Value::Signed(value as i64)),
+                    (true, true) => (Type::ULongLong, Value::Unsigned(value as u64)),
+                },
+                Some(MacroValue::Float { value, single }) => {
+                    let type_ = if single { Type::Float } else { Type::Double };
+                    (type_, Value::Float(value))
+                }
+                Some(MacroValue::Str(value)) => {
+                    let type_ = Type::Pointer {
+                        is_const: true,
+                        pointee: Box::new(Type::Char),
+                    };
+                    (type_, Value::Str(value))
+                }
+                None => continue,
+            };
+
+            namespace.constants.push(Constant {
+                name: name.clone(),
+                doc: None,
+                type_,
+                value,
+            });
+        }
+    }
+
+    fn parse_record(&mut self, record: clang::Type) -> Result<Record, Error> {
         let decl = record.declaration();
-        let name = decl.name().to_str().unwrap().to_string();
+        let name = decl.name().to_str()?.to_string();
         let kind = match decl.kind() {
             CursorKind::StructDecl | CursorKind::ClassDecl => RecordKind::Struct,
             CursorKind::UnionDecl => RecordKind::Union,
-            _ => unreachable!(),
+            _ => return Err(malformed(&decl.location(), "unexpected record declaration")),
         };
 
+        // Nested records are parsed first, so that the fields which use them can check whether
+        // they are aligned.
+        let mut inner = Namespace::new();
+        decl.visit_children(|cursor| self.visit(&mut inner, cursor))?;
+
         let mut fields = Vec::new();
+        let mut contains_aligned = false;
+        let mut natural_align = 1;
         let mut virtual_methods = Vec::new();
-        decl.visit_children(|cursor| -> Result<(), Box<dyn Error>> {
+        let mut destructor_position = None;
+        decl.visit_children(|cursor| -> Result<(), Error> {
             match cursor.kind() {
                 // Check for UnionDecl to handle anonymous unions
                 CursorKind::FieldDecl | CursorKind::UnionDecl => {
                     let name = if cursor.is_anonymous() {
                         None
                     } else {
-                        Some(cursor.name().to_str().unwrap().to_string())
+                        Some(cursor.name().to_str()?.to_string())
                     };
 
-                    let type_ = self.parse_type(cursor.type_().unwrap(), cursor.location())?;
+                    let field_type = cursor
+                        .type_()
+                        .ok_or_else(|| malformed(&cursor.location(), "field has no type"))?;
+                    if let Some(align) = field_type.align() {
+                        natural_align = natural_align.max(align);
+                    }
 
-                    fields.push(Field { name, type_ });
-                }
-                CursorKind::CxxMethod => {
-                    if cursor.is_virtual() {
-                        let mut arguments = Vec::new();
-
-                        for i in 0..cursor.num_arguments().unwrap() {
-                            let arg = cursor.argument(i).unwrap();
-
-                            let arg_type = arg.type_().unwrap();
-
-                            // Apply array-to-pointer decay for argument types
-                            let canonical_type = arg_type.canonical_type();
-                            let type_ = if canonical_type.kind() == TypeKind::ConstantArray {
-                                let is_const = canonical_type.is_const();
-                                let array_type = self.parse_type(arg_type, arg.location())?;
-                                Type::Pointer {
-                                    is_const,
-                                    pointee: Box::new(array_type),
-                                }
-                            } else {
-                                self.parse_type(arg_type, arg.location())?
-                            };
+                    let size = field_type.size();
+                    let bit_offset = cursor.offset_of_field();
 
-                            arguments.push(Argument {
-                                name: arg.name().to_str().unwrap().to_string(),
-                                type_,
-                            });
+                    let bitfield = if let Some(width) = cursor.bit_width() {
+                        if bit_offset.is_none() {
+                            return Err(malformed(&cursor.location(), "bitfield has no offset"));
                         }
 
-                        let result_type = self
-                            .parse_type(cursor.result_type().unwrap(), cursor.location())
-                            .unwrap();
+                        let signed = matches!(
+                            field_type.canonical_type().kind(),
+                            TypeKind::Char_S
+                                | TypeKind::SChar
+                                | TypeKind::Short
+                                | TypeKind::Int
+                                | TypeKind::Long
+                                | TypeKind::LongLong
+                        );
 
-                        virtual_methods.push(Method {
-                            name: cursor.name().to_str().unwrap().to_string(),
-                            arguments,
-                            result_type,
-                        });
+                        Some(Bitfield { width, signed })
+                    } else {
+                        None
+                    };
+
+                    let field_record = record_usr(&field_type)?;
+                    let type_ = self.parse_type(field_type, cursor.location())?;
+                    if let Some(usr) = field_record {
+                        contains_aligned |= self.aligned_records.contains(&usr);
                     }
+
+                    fields.push(Field {
+                        name,
+                        doc: parse_doc(cursor),
+                        bit_offset,
+                        size,
+                        bitfield,
+                        type_,
+                    });
+                }
+                CursorKind::CxxMethod if cursor.is_virtual() => {
+                    let location = cursor.location();
+
+                    let arguments = self.parse_arguments(cursor)?;
+
+                    let result_type = cursor
+                        .result_type()
+                        .ok_or_else(|| malformed(&location, "method has no result type"))?;
+
+                    // Unlike free functions, member functions on MSVC return all records through
+                    // a hidden pointer, regardless of their size. The Itanium ABI does the same
+                    // for records which aren't trivial for the purposes of calls.
+                    let returns_record = result_type.canonical_type().kind() == TypeKind::Record;
+                    let indirect_result = if !returns_record {
+                        None
+                    } else if self.msvc {
+                        Some(IndirectResult::AfterThis)
+                    } else if is_trivial_for_calls(&result_type)? {
+                        None
+                    } else if self.itanium_indirect_result {
+                        Some(IndirectResult::BeforeThis)
+                    } else {
+                        return Err(Error::Unsupported {
+                            location: location.to_string(),
+                            message: String::from(
+                                "virtual methods returning records which aren't trivially \
+                                 copyable are not supported for this target",
+                            ),
+                        });
+                    };
+
+                    let result_type = self.parse_type(result_type, cursor.location())?;
+
+                    virtual_methods.push(Method {
+                        name: cursor.name().to_str()?.to_string(),
+                        doc: parse_doc(cursor),
+                        arguments,
+                        result_type,
+                        overload: None,
+                        indirect_result,
+                    });
+                }
+                CursorKind::CxxDestructor if cursor.is_virtual() => {
+                    // Destructors aren't declared with an explicit calling convention, so they use
+                    // the default for member functions (`__thiscall` on 32-bit MSVC targets).
+                    let location = cursor.location();
+                    let calling_conv = cursor
+                        .type_()
+                        .ok_or_else(|| malformed(&location, "destructor has no type"))?
+                        .calling_conv();
+                    let calling_convention =
+                        calling_convention(calling_conv).ok_or_else(|| Error::Unsupported {
+                            location: location.to_string(),
+                            message: format!(
+                                "destructor uses the unsupported calling convention {calling_conv:?}"
+                            ),
+                        })?;
+
+                    destructor_position = Some((virtual_methods.len(), calling_convention));
                 }
                 _ => {}
             }
@@ -381,31 +974,174 @@ impl<'a> Parser<'a> {
 
         let bases = self.collect_bases(&decl)?;
 
-        let mut inner = Namespace::new();
-        decl.visit_children(|cursor| self.visit(&mut inner, cursor))?;
+        // A destructor which overrides a virtual destructor of a base class reuses its slots.
+        if destructor_position.is_some() && inherits_virtual_destructor(&decl)? {
+            destructor_position = None;
+        }
+
+        overload::number_overloads(&mut virtual_methods);
+        if self.msvc {
+            // Overloads declared after the destructor move ahead of it along with the first
+            // overload of their group.
+            if let Some((position, calling_convention)) = destructor_position {
+                let preceding = virtual_methods[..position]
+                    .iter()
+                    .map(|method| method.name.clone())
+                    .collect::<HashSet<_>>();
+                let moved = virtual_methods[position..]
+                    .iter()
+                    .filter(|method| preceding.contains(&method.name))
+                    .count();
+                destructor_position = Some((position + moved, calling_convention));
+            }
+
+            virtual_methods = overload::msvc_vtable_order(virtual_methods);
+        }
+
+        let virtual_destructor =
+            destructor_position.map(|(position, calling_convention)| VirtualDestructor {
+                position,
+                abi: if self.msvc { Abi::Msvc } else { Abi::Itanium },
+                calling_convention,
+            });
+
+        // libclang doesn't expose `#pragma pack` or alignment attributes directly, so detect them
+        // by comparing the record's alignment against the alignment its fields would naturally
+        // require. Records with virtual methods or base classes are assumed to be laid out
+        // naturally, since the natural alignment of their vtable pointers isn't known here.
+        let align = record.align().unwrap_or(natural_align);
+        let (packed, explicit_align) =
+            if virtual_methods.is_empty() && virtual_destructor.is_none() && bases.is_empty() {
+                if align < natural_align {
+                    (Some(align), None)
+                } else if align > natural_align {
+                    (None, Some(align))
+                } else {
+                    (None, None)
+                }
+            } else {
+                (None, None)
+            };
+
+        // Rust doesn't allow a `repr(packed)` type to contain a `repr(align)` type, so a packed
+        // record which contains an aligned record, or bitfields whose storage would need an
+        // explicit alignment, is emitted as an opaque array of integers with the same size and
+        // alignment instead.
+        let has_bitfields = fields.iter().any(|field| field.bitfield.is_some());
+        if packed.is_none() {
+            if explicit_align.is_some() || has_bitfields || contains_aligned {
+                self.aligned_records
+                    .insert(decl.usr().to_str()?.to_string());
+            }
+        } else if contains_aligned || (has_bitfields && align > 1) {
+            let unit = if matches!(align, 1 | 2 | 4 | 8) {
+                align
+            } else {
+                1
+            };
+            fields = vec![Field {
+                name: Some(String::from("__data")),
+                doc: None,
+                bit_offset: Some(0),
+                size: record.size(),
+                bitfield: None,
+                type_: Type::Array(record.size() / unit, Box::new(Type::Unsigned(unit))),
+            }];
+        }
 
         Ok(Record {
             name,
+            doc: parse_doc(&decl),
             kind,
+            size: record.size(),
+            align,
+            packed,
+            explicit_align,
             fields,
             bases,
             virtual_methods,
+            virtual_destructor,
+            iid: None,
             inner,
         })
     }
 
-    fn collect_bases(&self, decl: &Cursor) -> Result<Vec<Base>, Box<dyn Error>> {
+    fn parse_arguments(&mut self, cursor: &Cursor) -> Result<Vec<Argument>, Error> {
+        let mut arguments = Vec::new();
+
+        let location = cursor.location();
+
+        let num_arguments = cursor
+            .num_arguments()
+            .ok_or_else(|| malformed(&location, "function has no argument list"))?;
+        for i in 0..num_arguments {
+            let arg = cursor
+                .argument(i)
+                .ok_or_else(|| malformed(&location, "missing function argument"))?;
+
+            let arg_type = arg
+                .type_()
+                .ok_or_else(|| malformed(&arg.location(), "argument has no type"))?;
+
+            arguments.push(Argument {
+                name: arg.name().to_str()?.to_string(),
+                type_: self.parse_argument_type(arg_type, arg.location())?,
+            });
+        }
+
+        Ok(arguments)
+    }
+
+    fn parse_argument_type(
+        &mut self,
+        arg_type: clang::Type,
+        location: Location,
+    ) -> Result<Type, Error> {
+        // Apply array-to-pointer decay for argument types
+        let canonical_type = arg_type.canonical_type();
+        if canonical_type.kind() == TypeKind::ConstantArray {
+            let is_const = canonical_type.is_const();
+            let array_type = self.parse_type(arg_type, location)?;
+            Ok(Type::Pointer {
+                is_const,
+                pointee: Box::new(array_type),
+            })
+        } else {
+            self.parse_type(arg_type, location)
+        }
+    }
+
+    fn collect_bases(&mut self, decl: &Cursor) -> Result<Vec<Base>, Error> {
         let mut bases = Vec::new();
+        let mut end: usize = 0;
 
-        decl.visit_children(|cursor| -> Result<(), Box<dyn Error>> {
+        decl.visit_children(|cursor| -> Result<(), Error> {
             if cursor.kind() == CursorKind::CxxBaseSpecifier {
-                let decl = cursor.type_().unwrap().declaration();
+                let base_type = cursor
+                    .type_()
+                    .ok_or_else(|| malformed(&cursor.location(), "base class has no type"))?;
+                let base_decl = base_type.declaration();
+                self.current_dependencies.insert(item_path(&base_decl)?);
 
-                let name = decl.name();
-                let transitive_bases = self.collect_bases(&decl)?;
+                let name = base_decl.name();
+                let transitive_bases = self.collect_bases(&base_decl)?;
+
+                // Prefer the offset computed by clang, which accounts for packing and empty base
+                // classes. Older versions of libclang can't report base offsets, in which case
+                // non-virtual base class subobjects are assumed to be laid out in declaration
+                // order, each at the next suitably aligned offset.
+                let offset = match decl.offset_of_base(cursor) {
+                    Some(offset) => offset / 8,
+                    None => {
+                        let align = base_type.align().unwrap_or(1).max(1);
+                        end.div_ceil(align) * align
+                    }
+                };
+                end = offset + base_type.size();
 
                 bases.push(Base {
-                    name: name.to_str().unwrap().to_string(),
+                    name: name.to_str()?.to_string(),
+                    offset,
                     bases: transitive_bases,
                 });
             }
@@ -416,11 +1152,42 @@ impl<'a> Parser<'a> {
         Ok(bases)
     }
 
-    fn parse_type(
-        &mut self,
-        type_: clang::Type,
-        location: Location,
-    ) -> Result<Type, Box<dyn Error>> {
+    /// Returns the Rust type configured via [`Generator::map_type`] for a typedef, enum, or record
+    /// type, if there is one.
+    fn mapped_type(&self, type_: &clang::Type) -> Result<Option<Type>, Error> {
+        if self.options.type_map.is_empty() {
+            return Ok(None);
+        }
+
+        let name = qualified_name(&type_.declaration())?;
+        Ok(self
+            .options
+            .type_map
+            .get(&name)
+            .map(|rust_type| Type::Mapped {
+                name,
+                rust_type: rust_type.clone(),
+                size: type_.size(),
+                align: type_.align().unwrap_or(1),
+            }))
+    }
+
+    fn parse_type(&mut self, type_: clang::Type, location: Location) -> Result<Type, Error> {
+        if matches!(
+            type_.kind(),
+            TypeKind::Record | TypeKind::Enum | TypeKind::Typedef
+        ) {
+            if let Some(mapped) = self.mapped_type(&type_)? {
+                return Ok(mapped);
+            }
+        }
+
+        if self.options.fixed_width_integers {
+            if let Some(integer) = fixed_width_integer(&type_, &location)? {
+                return Ok(integer);
+            }
+        }
+
         match type_.kind() {
             TypeKind::Void => Ok(Type::Void),
             TypeKind::Bool => Ok(Type::Bool),
@@ -430,7 +1197,13 @@ impl<'a> Parser<'a> {
             TypeKind::UInt => Ok(Type::UInt),
             TypeKind::SChar => Ok(Type::SChar),
             TypeKind::Char16 => Ok(Type::Short),
-            TypeKind::WChar => Ok(Type::Unsigned(type_.size())),
+            TypeKind::WChar => match type_.size() {
+                size @ (1 | 2 | 4 | 8) => Ok(Type::Unsigned(size)),
+                size => Err(Error::UnsupportedType {
+                    location: location.to_string(),
+                    kind: format!("wchar_t of size {size}"),
+                }),
+            },
             TypeKind::ULong => Ok(Type::ULong),
             TypeKind::ULongLong => Ok(Type::ULongLong),
             TypeKind::Short => Ok(Type::Short),
@@ -440,14 +1213,18 @@ impl<'a> Parser<'a> {
             TypeKind::Float => Ok(Type::Float),
             TypeKind::Double => Ok(Type::Double),
             TypeKind::Pointer => {
-                let pointee = type_.pointee().unwrap();
+                let pointee = type_
+                    .pointee()
+                    .ok_or_else(|| malformed(&location, "pointer type has no pointee"))?;
                 Ok(Type::Pointer {
                     is_const: pointee.is_const(),
                     pointee: Box::new(self.parse_type(pointee, location)?),
                 })
             }
             TypeKind::LValueReference => {
-                let pointee = type_.pointee().unwrap();
+                let pointee = type_
+                    .pointee()
+                    .ok_or_else(|| malformed(&location, "reference type has no pointee"))?;
                 Ok(Type::Reference {
                     is_const: pointee.is_const(),
                     pointee: Box::new(self.parse_type(pointee, location)?),
@@ -456,41 +1233,334 @@ impl<'a> Parser<'a> {
             TypeKind::Record => {
                 let decl = type_.declaration();
                 if decl.is_anonymous() {
-                    Ok(Type::UnnamedRecord(self.parse_record(type_)?))
+                    Ok(Type::UnnamedRecord(Box::new(self.parse_record(type_)?)))
                 } else {
-                    let name = decl.name().to_str().unwrap().to_string();
-                    Ok(Type::Record(name))
+                    self.current_dependencies.insert(item_path(&decl)?);
+                    Ok(Type::Record {
+                        name: decl.name().to_str()?.to_string(),
+                        namespace: namespace_path(&decl)?,
+                    })
                 }
                 // name = format!("__type{}", namespace.unnamed_record_counter);
                 // namespace.unnamed_record_counter += 1;
             }
             TypeKind::Enum => {
                 let decl = type_.declaration();
-                Ok(Type::Typedef(decl.name().to_str().unwrap().to_string()))
+                self.current_dependencies.insert(item_path(&decl)?);
+                Ok(Type::Typedef {
+                    name: decl.name().to_str()?.to_string(),
+                    namespace: namespace_path(&decl)?,
+                })
             }
             TypeKind::Typedef => {
                 // Skip typedef declarations that are found in system headers
                 let declaration = type_.declaration();
                 if declaration.is_in_system_header() {
-                    let underlying_type = declaration.typedef_underlying_type().unwrap();
-                    return Ok(self.parse_type(underlying_type, location)?);
+                    let underlying_type = declaration
+                        .typedef_underlying_type()
+                        .ok_or_else(|| malformed(&location, "typedef has no underlying type"))?;
+                    return self.parse_type(underlying_type, location);
                 }
 
-                let name = type_.typedef_name().unwrap().to_str().unwrap().to_string();
-                Ok(Type::Typedef(name))
+                self.current_dependencies.insert(item_path(&declaration)?);
+                let name = type_
+                    .typedef_name()
+                    .ok_or_else(|| malformed(&location, "typedef has no name"))?;
+                Ok(Type::Typedef {
+                    name: name.to_str()?.to_string(),
+                    namespace: namespace_path(&declaration)?,
+                })
             }
             TypeKind::ConstantArray => {
-                let size = type_.array_size().unwrap();
-                let element_type =
-                    self.parse_type(type_.array_element_type().unwrap(), location)?;
+                let size = type_
+                    .array_size()
+                    .ok_or_else(|| malformed(&location, "array type has no size"))?;
+                let element_type = type_
+                    .array_element_type()
+                    .ok_or_else(|| malformed(&location, "array type has no element type"))?;
+                let element_type = self.parse_type(element_type, location)?;
                 Ok(Type::Array(size, Box::new(element_type)))
             }
-            TypeKind::Elaborated => self.parse_type(type_.named_type().unwrap(), location),
-            _ => Err(format!(
-                "error at {location}: unhandled type kind {:?}",
-                type_.kind()
-            )
-            .into()),
+            TypeKind::Elaborated => {
+                let named_type = type_
+                    .named_type()
+                    .ok_or_else(|| malformed(&location, "elaborated type has no named type"))?;
+                self.parse_type(named_type, location)
+            }
+            TypeKind::FunctionProto | TypeKind::FunctionNoProto => {
+                if type_.is_variadic() {
+                    return Err(Error::UnsupportedType {
+                        location: location.to_string(),
+                        kind: String::from("variadic function"),
+                    });
+                }
+
+                let Some(calling_convention) = calling_convention(type_.calling_conv()) else {
+                    return Err(Error::UnsupportedType {
+                        location: location.to_string(),
+                        kind: format!(
+                            "function with calling convention {:?}",
+                            type_.calling_conv()
+                        ),
+                    });
+                };
+
+                let num_arg_types = type_.num_arg_types().unwrap_or(0);
+                let mut arguments = Vec::new();
+                for i in 0..num_arg_types {
+                    let arg_type = type_
+                        .arg_type(i)
+                        .ok_or_else(|| malformed(&location, "missing function argument type"))?;
+                    arguments.push(self.parse_argument_type(arg_type, location)?);
+                }
+
+                let result_type = type_
+                    .result_type()
+                    .ok_or_else(|| malformed(&location, "function type has no result type"))?;
+                let result_type = self.parse_type(result_type, location)?;
+
+                Ok(Type::Function {
+                    calling_convention,
+                    arguments,
+                    result_type: Box::new(result_type),
+                })
+            }
+            kind => Err(Error::UnsupportedType {
+                location: location.to_string(),
+                kind: format!("{kind:?}"),
+            }),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{find_record, generate, parse};
+
+    const LINUX_X86_64: &str = "x86_64-unknown-linux-gnu";
+
+    #[test]
+    fn utf16_chars() {
+        assert_eq!(utf16_char(TypeKind::Char16, 2), Some(Type::Unsigned(2)));
+        assert_eq!(utf16_char(TypeKind::WChar, 2), Some(Type::Unsigned(2)));
+        assert_eq!(utf16_char(TypeKind::WChar, 4), None);
+        assert_eq!(utf16_char(TypeKind::Short, 2), None);
+    }
+
+    #[test]
+    fn packing_and_alignment() {
+        let source = r#"
+            #pragma pack(push, 2)
+            struct Packed { short a; int b; };
+            #pragma pack(pop)
+
+            struct alignas(16) Aligned { int a; };
+
+            #pragma pack(push, 2)
+            struct PackedAligned { Aligned inner; short b; };
+            #pragma pack(pop)
+
+            struct Natural { char a; int b; };
+        "#;
+        let Some(result) = parse(LINUX_X86_64, source) else {
+            return;
+        };
+        let namespace = result.unwrap();
+
+        let packed = find_record(&namespace, "Packed");
+        assert_eq!((packed.size, packed.align), (6, 2));
+        assert_eq!((packed.packed, packed.explicit_align), (Some(2), None));
+        assert_eq!(packed.fields[1].bit_offset, Some(16));
+
+        let aligned = find_record(&namespace, "Aligned");
+        assert_eq!((aligned.size, aligned.align), (16, 16));
+        assert_eq!((aligned.packed, aligned.explicit_align), (None, Some(16)));
+
+        // A packed record can't contain an aligned one in Rust, so its fields are replaced
+        let packed_aligned = find_record(&namespace, "PackedAligned");
+        assert_eq!((packed_aligned.size, packed_aligned.align), (18, 2));
+        assert_eq!(packed_aligned.packed, Some(2));
+        assert_eq!(packed_aligned.fields.len(), 1);
+        assert_eq!(
+            packed_aligned.fields[0].type_,
+            Type::Array(9, Box::new(Type::Unsigned(2)))
+        );
+
+        let natural = find_record(&namespace, "Natural");
+        assert_eq!((natural.size, natural.align), (8, 4));
+        assert_eq!((natural.packed, natural.explicit_align), (None, None));
+    }
+
+    #[test]
+    fn bitfield_storage_units() {
+        let source = r#"
+            struct Flags {
+                unsigned a : 3;
+                unsigned b : 5;
+                int c : 4;
+                bool d : 1;
+                int e;
+                unsigned f : 20;
+            };
+        "#;
+        let Some(result) = parse(LINUX_X86_64, source) else {
+            return;
+        };
+        let namespace = result.unwrap();
+
+        let flags = find_record(&namespace, "Flags");
+        assert_eq!((flags.size, flags.align), (12, 4));
+
+        let fields = flags
+            .fields
+            .iter()
+            .map(|field| {
+                let bitfield = field.bitfield.as_ref();
+                (
+                    field.name.as_deref().unwrap(),
+                    field.bit_offset,
+                    bitfield.map(|bitfield| (bitfield.width, bitfield.signed)),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                ("a", Some(0), Some((3, false))),
+                ("b", Some(3), Some((5, false))),
+                ("c", Some(8), Some((4, true))),
+                ("d", Some(12), Some((1, false))),
+                ("e", Some(32), None),
+                ("f", Some(64), Some((20, false))),
+            ]
+        );
+
+        let output = generate(Generator::default().layout_tests(true), &namespace);
+        assert!(output.contains(
+            "    pub _bitfield_1: ::com_scrape_types::BitfieldUnit<2>,\n    pub e: ::std::ffi::c_int,\n    pub _bitfield_2: ::com_scrape_types::BitfieldUnit<3>,\n"
+        ));
+        assert!(output.contains("self._bitfield_1.get_signed(8, 4) as ::std::ffi::c_int"));
+        assert!(output.contains("self._bitfield_1.get(12, 1) != 0"));
+        assert!(output.contains("self._bitfield_2.get(0, 20) as ::std::ffi::c_uint"));
+        assert!(output.contains("::std::mem::offset_of!(Flags, _bitfield_2) == 8);"));
+    }
+
+    #[test]
+    fn vtable_slot_order() {
+        let source = r#"
+            struct IFoo {
+                virtual void a() = 0;
+                virtual void set(int value) = 0;
+                virtual ~IFoo() {}
+                virtual void set(double value) = 0;
+                virtual void b() = 0;
+            };
+        "#;
+
+        let slots = |target| {
+            let namespace = parse(target, source)?.unwrap();
+            let record = find_record(&namespace, "IFoo");
+            let methods = record
+                .virtual_methods
+                .iter()
+                .map(|method| (method.name.clone(), method.overload))
+                .collect::<Vec<_>>();
+            let destructor = record.virtual_destructor.clone().unwrap();
+            Some((methods, destructor.position, destructor.abi))
+        };
+        let method = |name: &str, overload| (String::from(name), overload);
+
+        // Itanium keeps declaration order
+        let Some(itanium) = slots(LINUX_X86_64) else {
+            return;
+        };
+        assert_eq!(
+            itanium,
+            (
+                vec![
+                    method("a", None),
+                    method("set", Some(0)),
+                    method("set", Some(1)),
+                    method("b", None),
+                ],
+                2,
+                Abi::Itanium
+            )
+        );
+
+        // MSVC groups overloads at the first one in reverse order, and the later overload moves
+        // ahead of the destructor
+        let Some(msvc) = slots("x86_64-pc-windows-msvc") else {
+            return;
+        };
+        assert_eq!(
+            msvc,
+            (
+                vec![
+                    method("a", None),
+                    method("set", Some(1)),
+                    method("set", Some(0)),
+                    method("b", None),
+                ],
+                3,
+                Abi::Msvc
+            )
+        );
+    }
+
+    #[test]
+    fn indirect_results() {
+        let source = r#"
+            struct Trivial { int x, y; };
+            struct NonTrivial { NonTrivial(const NonTrivial&); int x; };
+            struct Defaulted { Defaulted(const Defaulted&) = default; int x; };
+
+            struct IFoo {
+                virtual int value() = 0;
+                virtual Trivial trivial() = 0;
+                virtual NonTrivial nonTrivial() = 0;
+                virtual Defaulted defaulted() = 0;
+            };
+        "#;
+
+        let indirect_results = |target| {
+            let namespace = parse(target, source)?.unwrap();
+            let record = find_record(&namespace, "IFoo");
+            let results = record
+                .virtual_methods
+                .iter()
+                .map(|method| method.indirect_result)
+                .collect::<Vec<_>>();
+            Some(results)
+        };
+
+        let Some(itanium) = indirect_results(LINUX_X86_64) else {
+            return;
+        };
+        assert_eq!(
+            itanium,
+            [None, None, Some(IndirectResult::BeforeThis), None]
+        );
+
+        // MSVC returns all records from member functions indirectly
+        let Some(msvc) = indirect_results("x86_64-pc-windows-msvc") else {
+            return;
+        };
+        assert_eq!(
+            msvc,
+            [
+                None,
+                Some(IndirectResult::AfterThis),
+                Some(IndirectResult::AfterThis),
+                Some(IndirectResult::AfterThis),
+            ]
+        );
+
+        // The hidden pointer is passed in a dedicated register on AArch64
+        let Some(result) = parse("aarch64-unknown-linux-gnu", source) else {
+            return;
+        };
+        assert!(matches!(result, Err(Error::Unsupported { .. })));
+    }
+}
@@ -1,7 +1,13 @@
-use std::collections::HashSet;
-use std::io::{self, ErrorKind, Write};
-
-use crate::parse::{Method, Namespace, Record, RecordKind, Type, Value};
+use std::collections::{BTreeSet, HashSet};
+use std::io::Write;
+
+use crate::error::Error;
+use crate::ir::{
+    Abi, Argument, Base, CallingConvention, IndirectResult, Method, Namespace, Record, RecordKind,
+    Type, Value, VirtualDestructor,
+};
+use crate::overload;
+use crate::targets::{self, TargetNamespace};
 use crate::Generator;
 
 struct UnnamedRecordScope {
@@ -25,16 +31,104 @@ pub struct RustPrinter<'a, W> {
     reserved: HashSet<&'static str>,
     indent_level: usize,
     unnamed_records: Vec<UnnamedRecordScope>,
+    /// The Rust types, sizes, and alignments of the mapped types used so far.
+    mapped_types: BTreeSet<(String, usize, usize)>,
+    /// The names of the namespaces enclosing the items currently being printed.
+    namespace_path: Vec<String>,
+}
+
+/// Collects every transitive base class of a record, along with the offset of the corresponding
+/// base class subobject. If a base class is reachable along multiple paths, only the first one
+/// (in declaration order, primary bases first) is kept.
+fn collect_ancestors<'b>(bases: &'b [Base], offset: usize, ancestors: &mut Vec<(&'b str, usize)>) {
+    for base in bases {
+        let base_offset = offset + base.offset;
+        if !ancestors.iter().any(|(name, _)| *name == base.name) {
+            ancestors.push((&base.name, base_offset));
+        }
+        collect_ancestors(&base.bases, base_offset, ancestors);
+    }
+}
+
+/// Collects the secondary (i.e. non-primary) base class subobjects of a record, in layout order.
+/// Secondary bases of the primary base come first, since the primary base is laid out at the start
+/// of the record.
+fn collect_secondary_bases<'b>(
+    bases: &'b [Base],
+    offset: usize,
+    secondary: &mut Vec<(&'b Base, usize)>,
+) {
+    if let Some(primary) = bases.first() {
+        collect_secondary_bases(&primary.bases, offset + primary.offset, secondary);
+    }
+
+    for base in bases.iter().skip(1) {
+        secondary.push((base, offset + base.offset));
+    }
+}
+
+/// Returns the Rust ABI string for a calling convention.
+fn calling_convention_abi(calling_convention: CallingConvention) -> &'static str {
+    match calling_convention {
+        CallingConvention::C => "C",
+        CallingConvention::Stdcall => "stdcall",
+        CallingConvention::Fastcall => "fastcall",
+        CallingConvention::Thiscall => "thiscall",
+        CallingConvention::Win64 => "win64",
+        CallingConvention::SysV64 => "sysv64",
+    }
+}
+
+/// Returns the number of vtable slots occupied by a virtual destructor under `abi`.
+fn destructor_slot_count(abi: Abi) -> usize {
+    match abi {
+        // The complete object destructor and the deleting destructor
+        Abi::Itanium => 2,
+        // A single deleting destructor, which takes a flags argument
+        Abi::Msvc => 1,
+    }
+}
+
+/// Converts a `camelCase` or `PascalCase` name to `snake_case`, keeping runs of capitals (such as
+/// `TUID` in `getTUIDString`) together.
+fn snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+
+    let mut result = String::with_capacity(name.len() + 4);
+    for (index, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && index > 0 {
+            let prev = chars[index - 1];
+            let next_is_lower = chars.get(index + 1).is_some_and(|c| c.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_is_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.push(c.to_ascii_lowercase());
+    }
+
+    result
+}
+
+fn has_multiple_inheritance(bases: &[Base]) -> bool {
+    bases.len() > 1
+        || bases
+            .iter()
+            .any(|base| has_multiple_inheritance(&base.bases))
 }
 
 impl<'a, W: Write> RustPrinter<'a, W> {
     pub fn new(sink: W, options: &'a Generator) -> RustPrinter<'a, W> {
         RustPrinter {
             sink,
-            options: options,
+            options,
             reserved: HashSet::from(["type"]),
             indent_level: 0,
             unnamed_records: Vec::new(),
+            mapped_types: BTreeSet::new(),
+            namespace_path: Vec::new(),
         }
     }
 
@@ -42,21 +136,219 @@ impl<'a, W: Write> RustPrinter<'a, W> {
         "    ".repeat(self.indent_level)
     }
 
+    fn escape(&self, name: String) -> String {
+        if self.reserved.contains(&*name) {
+            format!("r#{name}")
+        } else {
+            name
+        }
+    }
+
+    fn type_name(&self, name: &str) -> String {
+        self.options
+            .rename_type(name)
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Returns the path of the named type `name`, declared in the namespace `namespace`. Items
+    /// declared in the current namespace or one enclosing it are in scope through glob imports,
+    /// but items in other namespaces have to be referred to by their path from the root.
+    fn type_path(&self, name: &str, namespace: &[String]) -> String {
+        let mut path = String::new();
+        if !self.namespace_path.starts_with(namespace) {
+            for module in namespace {
+                path.push_str(&self.module_name(module));
+                path.push_str("::");
+            }
+        }
+        path.push_str(&self.type_name(name));
+
+        path
+    }
+
+    fn module_name(&self, name: &str) -> String {
+        self.options
+            .rename_module(name)
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    fn constant_name(&self, name: &str) -> String {
+        self.options
+            .rename_constant(name)
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Returns the (unescaped) name of a field of `record`.
+    fn field_name(&self, record: &str, field: &str) -> String {
+        self.options
+            .rename_field(record, field)
+            .unwrap_or_else(|| field.to_string())
+    }
+
+    /// Returns the name of an interface trait method.
+    fn method_name(&self, interface: &str, method: &str) -> String {
+        let name = self
+            .options
+            .rename_method(interface, method)
+            .unwrap_or_else(|| {
+                if self.options.snake_case_methods {
+                    snake_case(method)
+                } else {
+                    method.to_string()
+                }
+            });
+
+        self.escape(name)
+    }
+
+    /// Prints a `#[doc(alias)]` attribute with the C++ name of an item, if it was renamed.
+    fn print_alias(&mut self, indent: &str, original: &str, name: &str) -> Result<(), Error> {
+        if name.trim_start_matches("r#") != original {
+            writeln!(self.sink, "{indent}#[doc(alias = \"{original}\")]")?;
+        }
+
+        Ok(())
+    }
+
+    #[rustfmt::skip]
+    pub fn print_namespace(&mut self, namespace: &Namespace) -> Result<(), Error> {
+        let is_root = self.unnamed_records.is_empty();
+
+        self.push_unnamed_records("");
+
+        self.print_items(namespace)?;
+
+        let indent = self.indent();
+
+        for (name, child) in &namespace.children {
+            if !child.is_empty() {
+                let module_name = &self.module_name(name);
+                self.print_alias(&indent, name, module_name)?;
+                writeln!(self.sink, "{indent}pub mod {module_name} {{")?;
+                writeln!(self.sink, "{indent}    #[allow(unused_imports)]")?;
+                writeln!(self.sink, "{indent}    use super::*;")?;
+
+                self.indent_level += 1;
+                self.namespace_path.push(name.clone());
+                self.print_namespace(child)?;
+                self.namespace_path.pop();
+                self.indent_level -= 1;
+
+                writeln!(self.sink, "{indent}}}")?;
+            }
+        }
+
+        self.pop_unnamed_records()?;
+
+        if is_root {
+            let mapped_types = std::mem::take(&mut self.mapped_types);
+            self.print_mapped_type_tests(&mapped_types)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the definitions for several targets, as split by [`TargetNamespace::split`].
+    /// `targets` contains the `rustc` target triple for each target index.
     #[rustfmt::skip]
-    pub fn print_namespace(&mut self, namespace: &Namespace) -> io::Result<()> {
+    pub fn print_target_namespace(&mut self, namespace: &TargetNamespace, targets: &[&str]) -> Result<(), Error> {
+        let is_root = self.unnamed_records.is_empty();
+
         self.push_unnamed_records("");
 
         let indent = self.indent();
 
+        if is_root {
+            let cfg = targets::targets_cfg(targets.iter().copied());
+            writeln!(self.sink, "{indent}#[cfg(not({cfg}))]")?;
+            writeln!(self.sink, "{indent}compile_error!(\"bindings were not generated for this target\");")?;
+        }
+
+        self.print_items(&namespace.common)?;
+
+        for (index, (target_indices, group)) in namespace.groups.iter().enumerate() {
+            let cfg = targets::targets_cfg(target_indices.iter().map(|&index| targets[index]));
+
+            writeln!(self.sink, "{indent}#[cfg({cfg})]")?;
+            writeln!(self.sink, "{indent}mod __cfg_{index} {{")?;
+            writeln!(self.sink, "{indent}    #[allow(unused_imports)]")?;
+            writeln!(self.sink, "{indent}    use super::*;")?;
+
+            // Mapped types may only be used (and have a certain layout) on some targets, so their
+            // layout tests are gated as well
+            let outer_mapped_types = std::mem::take(&mut self.mapped_types);
+
+            self.indent_level += 1;
+            self.print_namespace(group)?;
+            let mapped_types = std::mem::replace(&mut self.mapped_types, outer_mapped_types);
+            self.print_mapped_type_tests(&mapped_types)?;
+            self.indent_level -= 1;
+
+            writeln!(self.sink, "{indent}}}")?;
+            writeln!(self.sink, "{indent}#[cfg({cfg})]")?;
+            writeln!(self.sink, "{indent}pub use __cfg_{index}::*;")?;
+        }
+
+        for (name, child) in &namespace.children {
+            if !child.is_empty() {
+                let module_name = &self.module_name(name);
+                self.print_alias(&indent, name, module_name)?;
+                writeln!(self.sink, "{indent}pub mod {module_name} {{")?;
+                writeln!(self.sink, "{indent}    #[allow(unused_imports)]")?;
+                writeln!(self.sink, "{indent}    use super::*;")?;
+
+                self.indent_level += 1;
+                self.namespace_path.push(name.clone());
+                self.print_target_namespace(child, targets)?;
+                self.namespace_path.pop();
+                self.indent_level -= 1;
+
+                writeln!(self.sink, "{indent}}}")?;
+            }
+        }
+
+        self.pop_unnamed_records()?;
+
+        if is_root {
+            let mapped_types = std::mem::take(&mut self.mapped_types);
+            self.print_mapped_type_tests(&mapped_types)?;
+        }
+
+        Ok(())
+    }
+
+    #[rustfmt::skip]
+    fn print_mapped_type_tests(&mut self, mapped_types: &BTreeSet<(String, usize, usize)>) -> Result<(), Error> {
+        if !self.options.layout_tests {
+            return Ok(());
+        }
+
+        let indent = self.indent();
+        for (rust_type, size, align) in mapped_types {
+            writeln!(self.sink, "{indent}const _: () = assert!(::std::mem::size_of::<{rust_type}>() == {size});")?;
+            writeln!(self.sink, "{indent}const _: () = assert!(::std::mem::align_of::<{rust_type}>() == {align});")?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the definitions in `namespace`, excluding nested namespaces.
+    #[rustfmt::skip]
+    fn print_items(&mut self, namespace: &Namespace) -> Result<(), Error> {
+        let indent = self.indent();
+
         for typedef in &namespace.typedefs {
-            let name = &typedef.name;
+            let name = &self.type_name(&typedef.name);
 
+            self.print_doc(&indent, typedef.doc.as_deref())?;
+            self.print_alias(&indent, &typedef.name, name)?;
             write!(self.sink, "{indent}pub type {name} = ")?;
             self.print_type(&typedef.type_)?;
             writeln!(self.sink, ";")?;
 
             if !typedef.inner.is_empty() {
-                writeln!(self.sink, "{indent}pub mod {name}_ {{")?;
+                let module_name = self.module_name(&format!("{name}_"));
+                writeln!(self.sink, "{indent}pub mod {module_name} {{")?;
                 writeln!(self.sink, "{indent}    #[allow(unused_imports)]")?;
                 writeln!(self.sink, "{indent}    use super::*;")?;
 
@@ -69,18 +361,34 @@ impl<'a, W: Write> RustPrinter<'a, W> {
         }
 
         for record in &namespace.records {
-            self.print_record(&record)?;
+            self.print_record(record)?;
+        }
+
+        for record in &namespace.opaque_records {
+            let name = &self.type_name(&record.name);
+            self.print_doc(&indent, record.doc.as_deref())?;
+            self.print_alias(&indent, &record.name, name)?;
+            writeln!(self.sink, "{indent}#[repr(C)]")?;
+            writeln!(self.sink, "{indent}pub struct {name} {{")?;
+            writeln!(self.sink, "{indent}    _private: [u8; 0],")?;
+            writeln!(self.sink, "{indent}    _marker: ::std::marker::PhantomData<(*mut u8, ::std::marker::PhantomPinned)>,")?;
+            writeln!(self.sink, "{indent}}}")?;
         }
 
         for constant in &namespace.constants {
-            let name = &constant.name;
+            let name = &self.constant_name(&constant.name);
+            self.print_doc(&indent, constant.doc.as_deref())?;
+            self.print_alias(&indent, &constant.name, name)?;
             write!(self.sink, "{indent}pub const {name}: ")?;
             self.print_type(&constant.type_)?;
             match &constant.value {
                 Value::Signed(value) => writeln!(self.sink, " = {value:?};")?,
                 Value::Unsigned(value) => writeln!(self.sink, " = {value:?};")?,
                 Value::Float(value) => writeln!(self.sink, " = {value:?};")?,
-                Value::Str(value) => writeln!(self.sink, " = b\"{value}\\0\".as_ptr() as *const ::std::ffi::c_char;")?,
+                Value::Str(value) => {
+                    let escaped = value.bytes().flat_map(std::ascii::escape_default).map(char::from).collect::<String>();
+                    writeln!(self.sink, " = b\"{escaped}\\0\".as_ptr() as *const ::std::ffi::c_char;")?
+                }
             }
         }
 
@@ -89,41 +397,63 @@ impl<'a, W: Write> RustPrinter<'a, W> {
             writeln!(self.sink, "{indent}{constant}")?;
         }
 
-        for (name, child) in &namespace.children {
-            if !child.is_empty() {
-                writeln!(self.sink, "{indent}pub mod {name} {{")?;
-                writeln!(self.sink, "{indent}    #[allow(unused_imports)]")?;
-                writeln!(self.sink, "{indent}    use super::*;")?;
+        let function_names = overload::unique_names(&namespace.functions, self.options.overload_naming);
 
-                self.indent_level += 1;
-                self.print_namespace(child)?;
-                self.indent_level -= 1;
+        for (function, name) in namespace.functions.iter().zip(&function_names) {
+            self.print_doc(&indent, function.doc.as_deref())?;
+            write!(self.sink, "{indent}pub type {name} = ")?;
+            let abi = calling_convention_abi(function.calling_convention);
+            self.print_fn_type(abi, function.arguments.iter().map(|arg| &arg.type_), &function.result_type)?;
+            writeln!(self.sink, ";")?;
+        }
 
-                writeln!(self.sink, "{indent}}}")?;
+        // Consecutive functions with the same calling convention share an `extern` block
+        let functions = namespace.functions.iter().zip(&function_names).collect::<Vec<_>>();
+        for functions in functions.chunk_by(|(a, _), (b, _)| a.calling_convention == b.calling_convention) {
+            let abi = calling_convention_abi(functions[0].0.calling_convention);
+            writeln!(self.sink, "{indent}extern \"{abi}\" {{")?;
+            for (function, name) in functions {
+                self.print_doc(&format!("{indent}    "), function.doc.as_deref())?;
+                if let Some(link_name) = &function.link_name {
+                    writeln!(self.sink, "{indent}    #[link_name = \"{link_name}\"]")?;
+                }
+                writeln!(self.sink, "{indent}    pub fn {name}(")?;
+
+                self.indent_level += 2;
+                self.print_args(&function.arguments)?;
+                self.indent_level -= 2;
+
+                write!(self.sink, "{indent}    )")?;
+                if let Type::Void = function.result_type {
+                } else {
+                    write!(self.sink, " -> ")?;
+                    self.print_type(&function.result_type)?;
+                }
+                writeln!(self.sink, ";")?;
             }
+            writeln!(self.sink, "{indent}}}")?;
         }
 
-        self.pop_unnamed_records()?;
-
         Ok(())
     }
 
     #[rustfmt::skip]
-    fn print_record(&mut self, record: &Record) -> io::Result<()> {
+    fn print_record(&mut self, record: &Record) -> Result<(), Error> {
         self.push_unnamed_records(&record.name);
 
         let needs_module = !record.inner.is_empty();
 
-        let name = &record.name;
+        let name = &self.type_name(&record.name);
 
         if needs_module {
             let indent = self.indent();
+            let module_name = self.module_name(&format!("{name}_"));
 
             writeln!(self.sink, "{indent}mod __{name}_wrapper {{")?;
             writeln!(self.sink, "{indent}    #[allow(unused_imports)]")?;
             writeln!(self.sink, "{indent}    use super::*;")?;
             writeln!(self.sink, "{indent}    #[allow(unused_imports)]")?;
-            writeln!(self.sink, "{indent}    use super::{name}_::*;")?;
+            writeln!(self.sink, "{indent}    use super::{module_name}::*;")?;
 
             self.indent_level += 1;
             self.print_record_body(record)?;
@@ -133,7 +463,7 @@ impl<'a, W: Write> RustPrinter<'a, W> {
             writeln!(self.sink, "{indent}}}")?;
             writeln!(self.sink, "{indent}pub use __{name}_wrapper::*;")?;
 
-            writeln!(self.sink, "{indent}pub mod {name}_ {{")?;
+            writeln!(self.sink, "{indent}pub mod {module_name} {{")?;
             writeln!(self.sink, "{indent}    #[allow(unused_imports)]")?;
             writeln!(self.sink, "{indent}    use super::*;")?;
 
@@ -153,79 +483,223 @@ impl<'a, W: Write> RustPrinter<'a, W> {
     }
 
     #[rustfmt::skip]
-    fn print_record_body(&mut self, record: &Record) -> io::Result<()> {
+    fn print_record_body(&mut self, record: &Record) -> Result<(), Error> {
         let indent = self.indent();
-        let name = &record.name;
+        let name = &self.type_name(&record.name);
         let record_kind = match record.kind {
             RecordKind::Struct => "struct",
             RecordKind::Union => "union",
         };
 
-        writeln!(self.sink, "{indent}#[repr(C)]")?;
+        // Bitfields are stored as byte arrays, so records containing them need an explicit
+        // alignment to match the C layout.
+        let has_bitfields = record.fields.iter().any(|field| field.bitfield.is_some());
+        let explicit_align = record.explicit_align.or(has_bitfields.then_some(record.align));
+
+        self.print_doc(&indent, record.doc.as_deref())?;
+        self.print_alias(&indent, &record.name, name)?;
+        if let Some(packed) = record.packed {
+            writeln!(self.sink, "{indent}#[repr(C, packed({packed}))]")?;
+        } else if let Some(align) = explicit_align {
+            writeln!(self.sink, "{indent}#[repr(C, align({align}))]")?;
+        } else {
+            writeln!(self.sink, "{indent}#[repr(C)]")?;
+        }
         writeln!(self.sink, "{indent}#[derive(Copy, Clone)]")?;
         writeln!(self.sink, "{indent}pub {record_kind} {name} {{")?;
 
-        if !record.virtual_methods.is_empty() {
+        let mut field_offsets = Vec::new();
+
+        if record.is_interface() {
             writeln!(self.sink, "{indent}    pub vtbl: *const {name}Vtbl,")?;
+
+            field_offsets.push((String::from("vtbl"), 0));
+
+            let mut secondary_bases = Vec::new();
+            collect_secondary_bases(&record.bases, 0, &mut secondary_bases);
+            for (index, (base, offset)) in secondary_bases.iter().enumerate() {
+                let base_name = self.type_name(&base.name);
+                let field_name = format!("__base{}", index + 1);
+                writeln!(self.sink, "{indent}    pub {field_name}: {base_name},")?;
+                field_offsets.push((field_name, *offset));
+            }
         }
 
+        // End offset in bytes of the previous field, if known.
+        let mut end = if record.is_interface() { None } else { Some(0) };
+
+        let mut bitfield_accessors = Vec::new();
+        let mut unit_counter = 0;
         let mut anon_counter = 0;
-        for field in &record.fields {
-            if let Some(field_name) = &field.name {
-                if self.reserved.contains(&**field_name) {
-                    write!(self.sink, "{indent}    pub r#{field_name}: ")?;
-                } else {
-                    write!(self.sink, "{indent}    pub {field_name}: ")?;
+        let mut index = 0;
+        while index < record.fields.len() {
+            let field = &record.fields[index];
+
+            // Group each run of adjacent bitfields into a single storage unit, which begins at the
+            // end of the previous field and extends to the end of the last bitfield in the run.
+            if field.bitfield.is_some() {
+                let run_end = record.fields[index..]
+                    .iter()
+                    .position(|field| field.bitfield.is_none())
+                    .map_or(record.fields.len(), |position| index + position);
+                let run = &record.fields[index..run_end];
+                index = run_end;
+
+                let first_offset = field.bit_offset.unwrap_or(0);
+                let unit_start = match record.kind {
+                    RecordKind::Struct => end.unwrap_or(first_offset / 8).min(first_offset / 8),
+                    RecordKind::Union => 0,
+                };
+                let unit_end = run
+                    .iter()
+                    .filter_map(|field| Some(field.bit_offset? + field.bitfield.as_ref()?.width))
+                    .max()
+                    .unwrap_or(0)
+                    .div_ceil(8)
+                    .max(unit_start);
+                end = Some(unit_end);
+
+                if unit_end == unit_start {
+                    continue;
+                }
+
+                unit_counter += 1;
+                let unit_name = format!("_bitfield_{unit_counter}");
+                let unit_size = unit_end - unit_start;
+                writeln!(self.sink, "{indent}    pub {unit_name}: ::com_scrape_types::BitfieldUnit<{unit_size}>,")?;
+                field_offsets.push((unit_name.clone(), unit_start));
+
+                for field in run {
+                    if let (Some(_), Some(bit_offset)) = (&field.name, field.bit_offset) {
+                        bitfield_accessors.push((unit_name.clone(), bit_offset - unit_start * 8, field));
+                    }
                 }
+
+                continue;
+            }
+            index += 1;
+
+            let original_name = if let Some(field_name) = &field.name {
+                field_name.clone()
             } else {
-                write!(self.sink, "{indent}    pub __field{anon_counter}: ")?;
                 anon_counter += 1;
+                format!("__field{}", anon_counter - 1)
+            };
+            let field_name = self.escape(self.field_name(&record.name, &original_name));
+
+            self.print_doc(&format!("{indent}    "), field.doc.as_deref())?;
+            if field.name.is_some() {
+                self.print_alias(&format!("{indent}    "), &original_name, &field_name)?;
             }
+            write!(self.sink, "{indent}    pub {field_name}: ")?;
             self.print_type(&field.type_)?;
             writeln!(self.sink, ",")?;
+
+            match field.bit_offset {
+                Some(bit_offset) if bit_offset % 8 == 0 => {
+                    field_offsets.push((field_name, bit_offset / 8));
+                    end = Some(bit_offset / 8 + field.size);
+                }
+                _ => end = None,
+            }
         }
 
         writeln!(self.sink, "{indent}}}")?;
         writeln!(self.sink, "{indent}unsafe impl Send for {name} {{}}")?;
         writeln!(self.sink, "{indent}unsafe impl Sync for {name} {{}}")?;
 
+        if !bitfield_accessors.is_empty() {
+            // Accessing union fields requires unsafe.
+            let (unsafe_start, unsafe_end) = match record.kind {
+                RecordKind::Struct => ("", ""),
+                RecordKind::Union => ("unsafe { ", " }"),
+            };
+
+            writeln!(self.sink, "{indent}impl {name} {{")?;
+            for (unit_name, bit_offset, field) in &bitfield_accessors {
+                let (Some(field_name), Some(bitfield)) = (&field.name, &field.bitfield) else {
+                    continue;
+                };
+                let width = bitfield.width;
+                let setter_name = format!("set_{}", self.field_name(&record.name, field_name));
+                let getter_name = self.escape(self.field_name(&record.name, field_name));
+
+                self.print_doc(&format!("{indent}    "), field.doc.as_deref())?;
+                self.print_alias(&format!("{indent}    "), field_name, &getter_name)?;
+                writeln!(self.sink, "{indent}    #[inline]")?;
+                write!(self.sink, "{indent}    pub fn {getter_name}(&self) -> ")?;
+                self.print_type(&field.type_)?;
+                writeln!(self.sink, " {{")?;
+                if let Type::Bool = field.type_ {
+                    writeln!(self.sink, "{indent}        {unsafe_start}self.{unit_name}.get({bit_offset}, {width}) != 0{unsafe_end}")?;
+                } else {
+                    let get = if bitfield.signed { "get_signed" } else { "get" };
+                    write!(self.sink, "{indent}        {unsafe_start}self.{unit_name}.{get}({bit_offset}, {width}) as ")?;
+                    self.print_type(&field.type_)?;
+                    writeln!(self.sink, "{unsafe_end}")?;
+                }
+                writeln!(self.sink, "{indent}    }}")?;
+
+                writeln!(self.sink, "{indent}    #[inline]")?;
+                write!(self.sink, "{indent}    pub fn {setter_name}(&mut self, value: ")?;
+                self.print_type(&field.type_)?;
+                writeln!(self.sink, ") {{")?;
+                writeln!(self.sink, "{indent}        {unsafe_start}self.{unit_name}.set({bit_offset}, {width}, value as u64){unsafe_end}")?;
+                writeln!(self.sink, "{indent}    }}")?;
+            }
+            writeln!(self.sink, "{indent}}}")?;
+        }
+
+        if self.options.layout_tests {
+            let size = record.size;
+            let align = record.align;
+            writeln!(self.sink, "{indent}const _: () = assert!(::std::mem::size_of::<{name}>() == {size});")?;
+            writeln!(self.sink, "{indent}const _: () = assert!(::std::mem::align_of::<{name}>() == {align});")?;
+            for (field_name, offset) in &field_offsets {
+                writeln!(self.sink, "{indent}const _: () = assert!(::std::mem::offset_of!({name}, {field_name}) == {offset});")?;
+            }
+        }
+
         Ok(())
     }
 
     #[rustfmt::skip]
-    fn print_interface(&mut self, record: &Record) -> io::Result<()> {
-        if !record.virtual_methods.is_empty() {
+    fn print_interface(&mut self, record: &Record) -> Result<(), Error> {
+        if record.is_interface() {
             let indent = self.indent();
-            let name = &record.name;
+            let name = &self.type_name(&record.name);
 
-            if record.bases.len() > 1 {
-                return Err(io::Error::new(
-                    ErrorKind::Other,
-                    format!("type {name} has more than one base class"),
-                ));
-            }
+            let mut ancestors = Vec::new();
+            collect_ancestors(&record.bases, 0, &mut ancestors);
 
-            {
-                let mut bases = &record.bases;
-                while let Some(base) = bases.first() {
-                    let base_name = &base.name;
+            let method_names = overload::unique_names(&record.virtual_methods, self.options.overload_naming);
+
+            for (base_name, offset) in &ancestors {
+                let base_name = self.type_name(base_name);
+                if *offset == 0 {
                     writeln!(self.sink, "{indent}unsafe impl ::com_scrape_types::Inherits<{base_name}> for {name} {{}}")?;
-                    bases = &base.bases;
+                } else {
+                    writeln!(self.sink, "{indent}unsafe impl ::com_scrape_types::Inherits<{base_name}> for {name} {{")?;
+                    writeln!(self.sink, "{indent}    const OFFSET: isize = {offset};")?;
+                    writeln!(self.sink, "{indent}}}")?;
                 }
             }
 
-            let iid_generator = self.options.iid_generator.as_ref().ok_or_else(|| {
-                io::Error::new(ErrorKind::Other, "no value provided for iid_generator")
-            })?;
-            let iid_string = iid_generator(name);
-            let query_interface_fn = self.options.query_interface_fn.as_ref().ok_or_else(|| {
-                io::Error::new(ErrorKind::Other, "no value provided for query_interface_fn")
+            let iid_string = match &record.iid {
+                Some(iid) => {
+                    let bytes = iid.iter().map(|byte| format!("{byte:#04x}")).collect::<Vec<_>>();
+                    format!("[{}]", bytes.join(", "))
+                }
+                None => self.options.get_iid_expr(&record.name).ok_or_else(|| Error::MissingIids(vec![record.name.clone()]))?,
+            };
+            let query_interface_fn = self.options.query_interface_fn.as_ref().ok_or(Error::MissingOption {
+                name: "query_interface_fn",
             })?;
-            let add_ref_fn = self.options.add_ref_fn.as_ref().ok_or_else(|| {
-                io::Error::new(ErrorKind::Other, "no value provided for add_ref_fn")
+            let add_ref_fn = self.options.add_ref_fn.as_ref().ok_or(Error::MissingOption {
+                name: "add_ref_fn",
             })?;
-            let release_fn = self.options.release_fn.as_ref().ok_or_else(|| {
-                io::Error::new(ErrorKind::Other, "no value provided for release_fn")
+            let release_fn = self.options.release_fn.as_ref().ok_or(Error::MissingOption {
+                name: "release_fn",
             })?;
 
             writeln!(self.sink, "{indent}impl ::com_scrape_types::Unknown for {name} {{")?;
@@ -249,38 +723,69 @@ impl<'a, W: Write> RustPrinter<'a, W> {
             writeln!(self.sink, "{indent}    #[inline]")?;
             writeln!(self.sink, "{indent}    fn inherits(iid: &::com_scrape_types::Guid) -> bool {{")?;
             write!(self.sink, "{indent}        iid == &Self::IID")?;
-            if let Some(base) = record.bases.first() {
-                let base_name = &base.name;
+            for base in &record.bases {
+                let base_name = self.type_name(&base.name);
                 write!(self.sink, " || {base_name}::inherits(iid)")?;
             }
-            writeln!(self.sink, "")?;
+            writeln!(self.sink)?;
             writeln!(self.sink, "{indent}    }}")?;
+            if has_multiple_inheritance(&record.bases) {
+                writeln!(self.sink, "{indent}    #[inline]")?;
+                writeln!(self.sink, "{indent}    fn base_offset(iid: &::com_scrape_types::Guid) -> Option<isize> {{")?;
+                writeln!(self.sink, "{indent}        if iid == &Self::IID {{")?;
+                writeln!(self.sink, "{indent}            return Some(0);")?;
+                writeln!(self.sink, "{indent}        }}")?;
+                for base in &record.bases {
+                    let base_name = self.type_name(&base.name);
+                    let offset = base.offset;
+                    writeln!(self.sink, "{indent}        if let Some(offset) = {base_name}::base_offset(iid) {{")?;
+                    if offset == 0 {
+                        writeln!(self.sink, "{indent}            return Some(offset);")?;
+                    } else {
+                        writeln!(self.sink, "{indent}            return Some({offset} + offset);")?;
+                    }
+                    writeln!(self.sink, "{indent}        }}")?;
+                }
+                writeln!(self.sink, "{indent}        None")?;
+                writeln!(self.sink, "{indent}    }}")?;
+            }
             writeln!(self.sink, "{indent}}}")?;
 
+            if let (Some(iid), Some(iid_type)) = (&record.iid, &self.options.iid_constant_type) {
+                let bytes = iid.iter().map(|byte| format!("{byte:#04x}u8 as _")).collect::<Vec<_>>();
+                writeln!(self.sink, "{indent}pub const {name}_iid: {iid_type} = [{}];", bytes.join(", "))?;
+            }
+
             writeln!(self.sink, "{indent}#[repr(C)]")?;
             writeln!(self.sink, "{indent}#[derive(Copy, Clone)]")?;
             writeln!(self.sink, "{indent}pub struct {name}Vtbl {{")?;
 
             if let Some(base) = record.bases.first() {
-                let base_name = &base.name;
+                let base_name = self.type_name(&base.name);
                 writeln!(self.sink, "{indent}    pub base: {base_name}Vtbl,")?;
             }
 
-            for method in &record.virtual_methods {
-                let method_name = &method.name;
+            for (index, (method, method_name)) in record.virtual_methods.iter().zip(&method_names).enumerate() {
+                if let Some(destructor) = record.virtual_destructor.as_ref().filter(|d| d.position == index) {
+                    self.print_destructor_slots(destructor, name)?;
+                }
+
+                self.print_doc(&format!("{indent}    "), method.doc.as_deref())?;
                 writeln!(
                     self.sink,
                     "{indent}    pub {method_name}: unsafe extern \"system\" fn("
                 )?;
 
-                writeln!(self.sink, "{indent}        this: *mut {name},")?;
-
                 self.indent_level += 2;
-                self.print_args(method)?;
+                self.print_this_arg(method, name)?;
+                self.print_args(&method.arguments)?;
                 self.indent_level -= 2;
 
                 write!(self.sink, "{indent}    )")?;
-                if let Type::Void = method.result_type {
+                if method.indirect_result.is_some() {
+                    write!(self.sink, " -> *mut ")?;
+                    self.print_type(&method.result_type)?;
+                } else if let Type::Void = method.result_type {
                 } else {
                     write!(self.sink, " -> ")?;
                     self.print_type(&method.result_type)?;
@@ -288,31 +793,58 @@ impl<'a, W: Write> RustPrinter<'a, W> {
                 writeln!(self.sink, ",")?;
             }
 
+            if let Some(destructor) = record.virtual_destructor.as_ref().filter(|d| d.position == record.virtual_methods.len()) {
+                self.print_destructor_slots(destructor, name)?;
+            }
+
             writeln!(self.sink, "{indent}}}")?;
 
+            // Each vtable consists of its primary base's vtable followed by one pointer per slot
+            if self.options.layout_tests {
+                let destructor_slots = record.virtual_destructor.as_ref().map_or(0, |d| destructor_slot_count(d.abi));
+                let slots = record.virtual_methods.len() + destructor_slots;
+                let slots_size = format!("::std::mem::size_of::<[*const (); {slots}]>()");
+                let expected = match record.bases.first() {
+                    Some(base) => format!("::std::mem::size_of::<{}Vtbl>() + {slots_size}", self.type_name(&base.name)),
+                    None => slots_size,
+                };
+                writeln!(self.sink, "{indent}const _: () = assert!(::std::mem::size_of::<{name}Vtbl>() == {expected});")?;
+            }
+
             if !self.options.skip_interface_traits.contains(&record.name) {
                 write!(self.sink, "{indent}pub trait {name}Trait")?;
                 {
-                    let mut bases = &record.bases;
-                    while let Some(base) = bases.first() {
-                        if !self.options.skip_interface_traits.contains(&base.name) {
-                            let base_name = &base.name;
-                            write!(self.sink, ": {base_name}Trait")?;
-                            break;
+                    let mut supertraits = Vec::new();
+                    for base in &record.bases {
+                        let mut base = Some(base);
+                        while let Some(current) = base {
+                            if !self.options.skip_interface_traits.contains(&current.name) {
+                                if !supertraits.contains(&&current.name) {
+                                    supertraits.push(&current.name);
+                                }
+                                break;
+                            }
+                            base = current.bases.first();
                         }
-                        bases = &base.bases;
+                    }
+                    for (index, base_name) in supertraits.iter().enumerate() {
+                        let base_name = self.type_name(base_name);
+                        let separator = if index == 0 { ":" } else { " +" };
+                        write!(self.sink, "{separator} {base_name}Trait")?;
                     }
                 }
                 writeln!(self.sink, " {{")?;
 
-                for method in &record.virtual_methods {
-                    let method_name = &method.name;
+                for (method, vtbl_field) in record.virtual_methods.iter().zip(&method_names) {
+                    let method_name = &self.method_name(&record.name, vtbl_field);
 
+                    self.print_doc(&format!("{indent}    "), method.doc.as_deref())?;
+                    self.print_alias(&format!("{indent}    "), &method.name, method_name)?;
                     writeln!(self.sink, "{indent}    unsafe fn {method_name}(")?;
                     writeln!(self.sink, "{indent}        &self,")?;
 
                     self.indent_level += 2;
-                    self.print_args(method)?;
+                    self.print_args(&method.arguments)?;
                     self.indent_level -= 2;
 
                     write!(self.sink, "{indent}    )")?;
@@ -330,27 +862,23 @@ impl<'a, W: Write> RustPrinter<'a, W> {
                 writeln!(self.sink, "{indent}where")?;
                 writeln!(self.sink, "{indent}    P: ::com_scrape_types::SmartPtr,")?;
                 writeln!(self.sink, "{indent}    P::Target: ::com_scrape_types::Inherits<{name}>,")?;
-                {
-                    let mut bases = &record.bases;
-                    while let Some(base) = bases.first() {
-                        if !self.options.skip_interface_traits.contains(&base.name) {
-                            let base_name = &base.name;
-                            writeln!(self.sink, "{indent}    P::Target: ::com_scrape_types::Inherits<{base_name}>,")?;
-                        }
-                        bases = &base.bases;
+                for (base_name, _) in &ancestors {
+                    if !self.options.skip_interface_traits.contains(*base_name) {
+                        let base_name = self.type_name(base_name);
+                        writeln!(self.sink, "{indent}    P::Target: ::com_scrape_types::Inherits<{base_name}>,")?;
                     }
                 }
                 writeln!(self.sink, "{indent}{{")?;
 
-                for method in &record.virtual_methods {
-                    let method_name = &method.name;
+                for (method, vtbl_field) in record.virtual_methods.iter().zip(&method_names) {
+                    let method_name = &self.method_name(&record.name, vtbl_field);
 
                     writeln!(self.sink, "{indent}    #[inline]")?;
                     writeln!(self.sink, "{indent}    unsafe fn {method_name}(")?;
                     writeln!(self.sink, "{indent}        &self,")?;
 
                     self.indent_level += 2;
-                    self.print_args(method)?;
+                    self.print_args(&method.arguments)?;
                     self.indent_level -= 2;
 
                     write!(self.sink, "{indent}    )")?;
@@ -360,93 +888,135 @@ impl<'a, W: Write> RustPrinter<'a, W> {
                         self.print_type(&method.result_type)?;
                     }
                     writeln!(self.sink, " {{")?;
-                    writeln!(self.sink, "{indent}        let ptr = self.ptr() as *mut {name};")?;
-                    writeln!(self.sink, "{indent}        ((*(*ptr).vtbl).{method_name})(")?;
+                    writeln!(self.sink, "{indent}        let ptr = (self.ptr() as *mut u8)")?;
+                    writeln!(self.sink, "{indent}            .offset(<P::Target as ::com_scrape_types::Inherits<{name}>>::OFFSET) as *mut {name};")?;
+                    if method.indirect_result.is_some() {
+                        writeln!(self.sink, "{indent}        let mut result = ::std::mem::MaybeUninit::uninit();")?;
+                    }
+                    writeln!(self.sink, "{indent}        ((*(*ptr).vtbl).{vtbl_field})(")?;
+                    if method.indirect_result == Some(IndirectResult::BeforeThis) {
+                        writeln!(self.sink, "{indent}            result.as_mut_ptr(),")?;
+                    }
                     writeln!(self.sink, "{indent}            ptr,")?;
+                    if method.indirect_result == Some(IndirectResult::AfterThis) {
+                        writeln!(self.sink, "{indent}            result.as_mut_ptr(),")?;
+                    }
 
                     self.indent_level += 3;
-                    self.print_arg_names(&method)?;
+                    self.print_arg_names(method)?;
                     self.indent_level -= 3;
 
-                    writeln!(self.sink, "{indent}        )")?;
+                    if method.indirect_result.is_some() {
+                        writeln!(self.sink, "{indent}        );")?;
+                        writeln!(self.sink, "{indent}        result.assume_init()")?;
+                    } else {
+                        writeln!(self.sink, "{indent}        )")?;
+                    }
                     writeln!(self.sink, "{indent}    }}")?;
                 }
 
                 writeln!(self.sink, "{indent}}}")?;
 
                 writeln!(self.sink, "{indent}impl {name} {{")?;
-                writeln!(self.sink, "{indent}    const fn make_vtbl<C, W, const OFFSET: isize>() -> {name}Vtbl")?;
+                writeln!(self.sink, "{indent}    const fn make_vtbl<C, W, O, const ADJUST: isize>() -> {name}Vtbl")?;
                 writeln!(self.sink, "{indent}    where")?;
                 writeln!(self.sink, "{indent}        C: {name}Trait + ::com_scrape_types::Class,")?;
                 writeln!(self.sink, "{indent}        W: ::com_scrape_types::Wrapper<C>,")?;
+                writeln!(self.sink, "{indent}        O: ::com_scrape_types::Offset,")?;
                 writeln!(self.sink, "{indent}    {{")?;
 
                 #[rustfmt::skip]
-                for method in &record.virtual_methods {
-                    let method_name = &method.name;
-
-                    writeln!(self.sink, "{indent}        unsafe extern \"system\" fn {method_name}<C, W, const OFFSET: isize>(")?;
-                    writeln!(self.sink, "{indent}            this: *mut {name},")?;
+                for (method, vtbl_field) in record.virtual_methods.iter().zip(&method_names) {
+                    let method_name = &self.method_name(&record.name, vtbl_field);
 
+                    writeln!(self.sink, "{indent}        unsafe extern \"system\" fn {vtbl_field}<C, W, O, const ADJUST: isize>(")?;
                     self.indent_level += 3;
-                    self.print_args(method)?;
+                    self.print_this_arg(method, name)?;
+                    self.print_args(&method.arguments)?;
                     self.indent_level -= 3;
 
                     write!(self.sink, "{indent}        )")?;
-                    if let Type::Void = method.result_type {
+                    if method.indirect_result.is_some() {
+                        write!(self.sink, " -> *mut ")?;
+                        self.print_type(&method.result_type)?;
+                    } else if let Type::Void = method.result_type {
                     } else {
                         write!(self.sink, " -> ")?;
                         self.print_type(&method.result_type)?;
                     }
-                    writeln!(self.sink, "")?;
+                    writeln!(self.sink)?;
                     writeln!(self.sink, "{indent}        where")?;
                     writeln!(self.sink, "{indent}            C: {name}Trait + ::com_scrape_types::Class,")?;
                     writeln!(self.sink, "{indent}            W: ::com_scrape_types::Wrapper<C>,")?;
+                    writeln!(self.sink, "{indent}            O: ::com_scrape_types::Offset,")?;
                     writeln!(self.sink, "{indent}        {{")?;
-                    writeln!(self.sink, "{indent}            let header_ptr = (this as *mut u8).offset(-OFFSET) as *mut Header<C>;")?;
+                    writeln!(self.sink, "{indent}            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;")?;
                     writeln!(self.sink, "{indent}            let ptr = <W as ::com_scrape_types::Wrapper<C>>::data_from_header(header_ptr);")?;
-                    writeln!(self.sink, "{indent}            (*ptr).{method_name}(")?;
+                    if method.indirect_result.is_some() {
+                        writeln!(self.sink, "{indent}            __result.write((*ptr).{method_name}(")?;
+                    } else {
+                        writeln!(self.sink, "{indent}            (*ptr).{method_name}(")?;
+                    }
 
                     self.indent_level += 4;
                     self.print_arg_names(method)?;
                     self.indent_level -= 4;
 
-                    writeln!(self.sink, "{indent}            )")?;
+                    if method.indirect_result.is_some() {
+                        writeln!(self.sink, "{indent}            ));")?;
+                        writeln!(self.sink, "{indent}            __result")?;
+                    } else {
+                        writeln!(self.sink, "{indent}            )")?;
+                    }
                     writeln!(self.sink, "{indent}        }}")?;
                 };
 
+                if let Some(destructor) = &record.virtual_destructor {
+                    self.print_destructor_thunks(destructor, name)?;
+                }
+
                 writeln!(self.sink, "{indent}        {name}Vtbl {{")?;
                 if let Some(base) = record.bases.first() {
-                    let base_name = &base.name;
-                    writeln!(self.sink, "{indent}            base: {base_name}::make_vtbl::<C, W, OFFSET>(),")?;
+                    let base_name = self.type_name(&base.name);
+                    writeln!(self.sink, "{indent}            base: {base_name}::make_vtbl::<C, W, O, ADJUST>(),")?;
                 }
 
-                for method in &record.virtual_methods {
-                    let method_name = &method.name;
+                for method_name in &method_names {
                     writeln!(
                         self.sink,
-                        "{indent}            {method_name}: {method_name}::<C, W, OFFSET>,"
+                        "{indent}            {method_name}: {method_name}::<C, W, O, ADJUST>,"
                     )?;
                 }
 
+                if let Some(destructor) = &record.virtual_destructor {
+                    if destructor.abi == Abi::Itanium {
+                        writeln!(self.sink, "{indent}            __destructor: __destructor::<C, W, O, ADJUST>,")?;
+                    }
+                    writeln!(self.sink, "{indent}            __deleting_destructor: __deleting_destructor::<C, W, O, ADJUST>,")?;
+                }
+
                 writeln!(self.sink, "{indent}        }}")?;
 
                 writeln!(self.sink, "{indent}    }}")?;
                 writeln!(self.sink, "{indent}}}")?;
 
-                writeln!(self.sink, "{indent}unsafe impl<C, W, const OFFSET: isize> ::com_scrape_types::Construct<C, W, OFFSET> for {name}")?;
+                writeln!(self.sink, "{indent}unsafe impl<C, W, O> ::com_scrape_types::Construct<C, W, O> for {name}")?;
                 writeln!(self.sink, "{indent}where")?;
                 writeln!(
                     self.sink,
                     "{indent}    C: {name}Trait + ::com_scrape_types::Class,"
                 )?;
                 writeln!(self.sink, "{indent}    W: ::com_scrape_types::Wrapper<C>,")?;
+                writeln!(self.sink, "{indent}    O: ::com_scrape_types::Offset,")?;
                 writeln!(self.sink, "{indent}{{")?;
                 writeln!(self.sink, "{indent}    const OBJ: Self = {name} {{")?;
                 writeln!(
                     self.sink,
-                    "{indent}        vtbl: &Self::make_vtbl::<C, W, OFFSET>(),"
+                    "{indent}        vtbl: &Self::make_vtbl::<C, W, O, 0>(),"
                 )?;
+                self.indent_level += 2;
+                self.print_secondary_objects(&record.bases, 0)?;
+                self.indent_level -= 2;
                 writeln!(self.sink, "{indent}    }};")?;
                 writeln!(self.sink, "{indent}}}")?;
             }
@@ -455,12 +1025,140 @@ impl<'a, W: Write> RustPrinter<'a, W> {
         Ok(())
     }
 
-    fn print_args(&mut self, method: &Method) -> io::Result<()> {
+    /// Prints the vtable fields for the slots occupied by a virtual destructor. These are never
+    /// called from Rust, and only serve to keep the vtable layout in sync with C++.
+    #[rustfmt::skip]
+    fn print_destructor_slots(&mut self, destructor: &VirtualDestructor, name: &str) -> Result<(), Error> {
+        let indent = self.indent();
+        let abi = calling_convention_abi(destructor.calling_convention);
+
+        match destructor.abi {
+            Abi::Itanium => {
+                writeln!(self.sink, "{indent}    pub __destructor: unsafe extern \"{abi}\" fn(this: *mut {name}),")?;
+                writeln!(self.sink, "{indent}    pub __deleting_destructor: unsafe extern \"{abi}\" fn(this: *mut {name}),")?;
+            }
+            Abi::Msvc => {
+                writeln!(self.sink, "{indent}    pub __deleting_destructor: unsafe extern \"{abi}\" fn(this: *mut {name}, flags: ::std::ffi::c_uint) -> *mut ::std::ffi::c_void,")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints the `make_vtbl` thunks for the slots occupied by a virtual destructor. Deleting the
+    /// object releases the reference held by the caller, and the complete object destructor does
+    /// nothing, since the object is dropped once its last reference is released. In other words,
+    /// `delete p` from C++ decrements the reference count rather than destroying the object.
+    #[rustfmt::skip]
+    fn print_destructor_thunks(&mut self, destructor: &VirtualDestructor, name: &str) -> Result<(), Error> {
+        let indent = self.indent();
+        let abi = calling_convention_abi(destructor.calling_convention);
+
+        let (args, result, release_indent) = match destructor.abi {
+            Abi::Itanium => ("", "", ""),
+            Abi::Msvc => (", flags: ::std::ffi::c_uint", " -> *mut ::std::ffi::c_void", "    "),
+        };
+
+        if destructor.abi == Abi::Itanium {
+            writeln!(self.sink, "{indent}        unsafe extern \"{abi}\" fn __destructor<C, W, O, const ADJUST: isize>(_this: *mut {name})")?;
+            writeln!(self.sink, "{indent}        where")?;
+            writeln!(self.sink, "{indent}            C: {name}Trait + ::com_scrape_types::Class,")?;
+            writeln!(self.sink, "{indent}            W: ::com_scrape_types::Wrapper<C>,")?;
+            writeln!(self.sink, "{indent}            O: ::com_scrape_types::Offset,")?;
+            writeln!(self.sink, "{indent}        {{")?;
+            writeln!(self.sink, "{indent}        }}")?;
+        }
+
+        writeln!(self.sink, "{indent}        unsafe extern \"{abi}\" fn __deleting_destructor<C, W, O, const ADJUST: isize>(this: *mut {name}{args}){result}")?;
+        writeln!(self.sink, "{indent}        where")?;
+        writeln!(self.sink, "{indent}            C: {name}Trait + ::com_scrape_types::Class,")?;
+        writeln!(self.sink, "{indent}            W: ::com_scrape_types::Wrapper<C>,")?;
+        writeln!(self.sink, "{indent}            O: ::com_scrape_types::Offset,")?;
+        writeln!(self.sink, "{indent}        {{")?;
+        if destructor.abi == Abi::Msvc {
+            // The low bit of `flags` indicates that the object should be deallocated
+            writeln!(self.sink, "{indent}            if flags & 1 != 0 {{")?;
+        }
+        let inner = format!("{indent}{release_indent}");
+        writeln!(self.sink, "{inner}            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;")?;
+        writeln!(self.sink, "{inner}            let ptr = <W as ::com_scrape_types::Wrapper<C>>::data_from_header(header_ptr);")?;
+        writeln!(self.sink, "{inner}            <W as ::com_scrape_types::Wrapper<C>>::release(ptr);")?;
+        if destructor.abi == Abi::Msvc {
+            writeln!(self.sink, "{indent}            }}")?;
+            writeln!(self.sink, "{indent}            this as *mut ::std::ffi::c_void")?;
+        }
+        writeln!(self.sink, "{indent}        }}")?;
+
+        Ok(())
+    }
+
+    /// Prints the initializers for the secondary base class subobjects in an interface's
+    /// `Construct::OBJ`, where `adjust` is the offset of the object containing them.
+    fn print_secondary_objects(&mut self, bases: &[Base], adjust: usize) -> Result<(), Error> {
+        let indent = self.indent();
+
+        let mut secondary_bases = Vec::new();
+        collect_secondary_bases(bases, 0, &mut secondary_bases);
+
+        for (index, (base, offset)) in secondary_bases.iter().enumerate() {
+            let base_name = self.type_name(&base.name);
+            let field_index = index + 1;
+            let adjust = adjust + offset;
+
+            writeln!(self.sink, "{indent}__base{field_index}: {base_name} {{")?;
+            writeln!(
+                self.sink,
+                "{indent}    vtbl: &{base_name}::make_vtbl::<C, W, O, {adjust}>(),"
+            )?;
+            self.indent_level += 1;
+            self.print_secondary_objects(&base.bases, adjust)?;
+            self.indent_level -= 1;
+            writeln!(self.sink, "{indent}}},")?;
+        }
+
+        Ok(())
+    }
+
+    fn print_doc(&mut self, indent: &str, doc: Option<&str>) -> Result<(), Error> {
+        if let Some(doc) = doc {
+            for line in doc.lines() {
+                if line.is_empty() {
+                    writeln!(self.sink, "{indent}///")?;
+                } else {
+                    writeln!(self.sink, "{indent}/// {line}")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints the `this` argument of a method, along with the hidden pointer through which it
+    /// returns its result, if any, in the order the ABI passes them.
+    fn print_this_arg(&mut self, method: &Method, name: &str) -> Result<(), Error> {
+        let indent = self.indent();
+
+        if method.indirect_result == Some(IndirectResult::BeforeThis) {
+            write!(self.sink, "{indent}__result: *mut ")?;
+            self.print_type(&method.result_type)?;
+            writeln!(self.sink, ",")?;
+        }
+        writeln!(self.sink, "{indent}this: *mut {name},")?;
+        if method.indirect_result == Some(IndirectResult::AfterThis) {
+            write!(self.sink, "{indent}__result: *mut ")?;
+            self.print_type(&method.result_type)?;
+            writeln!(self.sink, ",")?;
+        }
+
+        Ok(())
+    }
+
+    fn print_args(&mut self, arguments: &[Argument]) -> Result<(), Error> {
         let mut unnamed_counter = 0;
 
         let indent = self.indent();
 
-        for arg in &method.arguments {
+        for arg in arguments {
             let arg_name = &arg.name;
             if arg.name.is_empty() {
                 write!(self.sink, "{indent}_{unnamed_counter}: ")?;
@@ -477,7 +1175,7 @@ impl<'a, W: Write> RustPrinter<'a, W> {
         Ok(())
     }
 
-    fn print_arg_names(&mut self, method: &Method) -> io::Result<()> {
+    fn print_arg_names(&mut self, method: &Method) -> Result<(), Error> {
         let mut unnamed_counter = 0;
 
         let indent = self.indent();
@@ -497,7 +1195,7 @@ impl<'a, W: Write> RustPrinter<'a, W> {
         Ok(())
     }
 
-    fn print_type(&mut self, type_: &Type) -> io::Result<()> {
+    fn print_type(&mut self, type_: &Type) -> Result<(), Error> {
         match type_ {
             Type::Void => write!(self.sink, "::std::ffi::c_void")?,
             Type::Bool => write!(self.sink, "bool")?,
@@ -517,27 +1215,25 @@ impl<'a, W: Write> RustPrinter<'a, W> {
                 2 => write!(self.sink, "u16")?,
                 4 => write!(self.sink, "u32")?,
                 8 => write!(self.sink, "u64")?,
-                _ => {
-                    return Err(io::Error::new(
-                        ErrorKind::Other,
-                        format!("unexpected size {size} for unsigned integer"),
-                    ))
-                }
+                _ => return Err(Error::InvalidIr(format!("unsigned integer of size {size}"))),
             },
             Type::Signed(size) => match size {
                 1 => write!(self.sink, "i8")?,
                 2 => write!(self.sink, "i16")?,
                 4 => write!(self.sink, "i32")?,
                 8 => write!(self.sink, "i64")?,
-                _ => {
-                    return Err(io::Error::new(
-                        ErrorKind::Other,
-                        format!("unexpected size {size} for signed integer"),
-                    ))
-                }
+                _ => return Err(Error::InvalidIr(format!("signed integer of size {size}"))),
             },
             Type::Float => write!(self.sink, "f32")?,
             Type::Double => write!(self.sink, "f64")?,
+            // Function pointers are nullable in C, but not in Rust
+            Type::Pointer { pointee, .. } | Type::Reference { pointee, .. }
+                if matches!(**pointee, Type::Function { .. }) =>
+            {
+                write!(self.sink, "Option<")?;
+                self.print_type(pointee)?;
+                write!(self.sink, ">")?;
+            }
             Type::Pointer { is_const, pointee } | Type::Reference { is_const, pointee } => {
                 if *is_const {
                     write!(self.sink, "*const ")?;
@@ -546,22 +1242,67 @@ impl<'a, W: Write> RustPrinter<'a, W> {
                 }
                 self.print_type(pointee)?;
             }
-            Type::Record(name) => write!(self.sink, "{name}")?,
+            Type::Record { name, namespace } => {
+                write!(self.sink, "{}", self.type_path(name, namespace))?
+            }
             Type::UnnamedRecord(record) => {
                 let scope = self.unnamed_record_scope_mut();
                 let name = scope.next_name();
-                let mut record = record.clone();
+                let mut record = (**record).clone();
                 record.name = name.clone();
                 scope.add_record(record);
 
-                write!(self.sink, "{name}")?;
+                write!(self.sink, "{}", self.type_name(&name))?;
+            }
+            Type::Typedef { name, namespace } => {
+                write!(self.sink, "{}", self.type_path(name, namespace))?
             }
-            Type::Typedef(name) => write!(self.sink, "{name}")?,
             Type::Array(size, elem) => {
                 write!(self.sink, "[")?;
                 self.print_type(elem)?;
                 write!(self.sink, "; {size}]")?
             }
+            Type::Function {
+                calling_convention,
+                arguments,
+                result_type,
+            } => {
+                let abi = calling_convention_abi(*calling_convention);
+                self.print_fn_type(abi, arguments.iter(), result_type)?
+            }
+            Type::Mapped {
+                rust_type,
+                size,
+                align,
+                ..
+            } => {
+                self.mapped_types.insert((rust_type.clone(), *size, *align));
+                write!(self.sink, "{rust_type}")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_fn_type<'t>(
+        &mut self,
+        abi: &str,
+        arguments: impl Iterator<Item = &'t Type>,
+        result_type: &Type,
+    ) -> Result<(), Error> {
+        write!(self.sink, "unsafe extern \"{abi}\" fn(")?;
+        for (i, arg) in arguments.enumerate() {
+            if i > 0 {
+                write!(self.sink, ", ")?;
+            }
+            self.print_type(arg)?;
+        }
+        write!(self.sink, ")")?;
+
+        if let Type::Void = result_type {
+        } else {
+            write!(self.sink, " -> ")?;
+            self.print_type(result_type)?;
         }
 
         Ok(())
@@ -578,7 +1319,7 @@ impl<'a, W: Write> RustPrinter<'a, W> {
         self.unnamed_records.last_mut().unwrap()
     }
 
-    fn pop_unnamed_records(&mut self) -> io::Result<()> {
+    fn pop_unnamed_records(&mut self) -> Result<(), Error> {
         let unnamed_records = self.unnamed_records.pop().unwrap();
 
         for record in unnamed_records.records {
@@ -588,3 +1329,453 @@ impl<'a, W: Write> RustPrinter<'a, W> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::Function;
+    use crate::test_util::{field, generate, interface, method, record, typedef};
+    use crate::OverloadNaming;
+
+    /// Returns a generator with the reference counting functions required for interfaces set.
+    fn interface_generator() -> Generator {
+        Generator::default()
+            .query_interface_fn("query_interface")
+            .add_ref_fn("add_ref")
+            .release_fn("release")
+    }
+
+    #[test]
+    fn snake_case_names() {
+        assert_eq!(snake_case("getBusCount"), "get_bus_count");
+        assert_eq!(snake_case("setIoMode"), "set_io_mode");
+        assert_eq!(snake_case("getTUIDString"), "get_tuid_string");
+        assert_eq!(snake_case("getParameterID"), "get_parameter_id");
+        assert_eq!(snake_case("getValue2Normalized"), "get_value2_normalized");
+        assert_eq!(snake_case("terminate"), "terminate");
+    }
+
+    #[test]
+    fn renamed_items() {
+        let mut namespace = Namespace::new();
+        namespace.records.push(interface(
+            "IComponent",
+            vec![Method {
+                result_type: Type::Int,
+                ..method("getBusCount")
+            }],
+        ));
+        namespace.records.push(Record {
+            size: 4,
+            align: 4,
+            fields: vec![field("channelCount", 0, 4, Type::Int)],
+            ..record("BusInfo")
+        });
+
+        let generator = interface_generator()
+            .type_renamer(|name| (name == "IComponent").then(|| String::from("Component")))
+            .field_renamer(|_, field| Some(snake_case(field)))
+            .snake_case_methods(true);
+        let output = generate(generator, &namespace);
+
+        assert!(output.contains("#[doc(alias = \"IComponent\")]\n#[repr(C)]"));
+        assert!(output.contains("pub struct ComponentVtbl {"));
+        assert!(output.contains("pub getBusCount: unsafe extern \"system\" fn("));
+        assert!(output.contains("pub trait ComponentTrait {"));
+        assert!(
+            output.contains("    #[doc(alias = \"getBusCount\")]\n    unsafe fn get_bus_count(")
+        );
+        assert!(output.contains("((*(*ptr).vtbl).getBusCount)("));
+        assert!(output.contains("    #[doc(alias = \"channelCount\")]\n    pub channel_count: "));
+        assert!(!output.contains("alias = \"BusInfo\""));
+    }
+
+    #[test]
+    fn invalid_integer_size() {
+        let mut namespace = Namespace::new();
+        namespace.constants.push(crate::ir::Constant {
+            name: String::from("kValue"),
+            doc: None,
+            type_: Type::Unsigned(3),
+            value: Value::Unsigned(1),
+        });
+
+        let mut output = Vec::new();
+        let result = Generator::default().generate_from_ir(&namespace, &mut output);
+        match result {
+            Err(Error::InvalidIr(message)) => assert_eq!(message, "unsigned integer of size 3"),
+            _ => panic!("expected Error::InvalidIr"),
+        }
+    }
+
+    #[test]
+    fn mapped_types() {
+        let tuid = Type::Mapped {
+            name: String::from("Steinberg::TUID"),
+            rust_type: String::from("crate::Tuid"),
+            size: 16,
+            align: 1,
+        };
+
+        let mut namespace = Namespace::new();
+        namespace.records.push(Record {
+            size: 32,
+            align: 1,
+            fields: vec![
+                field("cid", 0, 16, tuid.clone()),
+                field("other", 128, 16, tuid),
+            ],
+            ..record("PClassInfo")
+        });
+
+        let mut root = Namespace::new();
+        root.children.insert(String::from("Steinberg"), namespace);
+
+        let output = generate(Generator::default().layout_tests(true), &root);
+
+        assert!(output.contains("pub cid: crate::Tuid,\n        pub other: crate::Tuid,\n"));
+        assert!(output.ends_with(
+            "const _: () = assert!(::std::mem::size_of::<crate::Tuid>() == 16);\n\
+             const _: () = assert!(::std::mem::align_of::<crate::Tuid>() == 1);\n"
+        ));
+        assert_eq!(output.matches("size_of::<crate::Tuid>()").count(), 1);
+    }
+
+    #[test]
+    fn packed_and_aligned_records() {
+        let record_with_layout = |name: &str, size, align, packed, explicit_align, fields| Record {
+            size,
+            align,
+            packed,
+            explicit_align,
+            fields,
+            ..record(name)
+        };
+
+        let mut namespace = Namespace::new();
+        namespace.records.push(record_with_layout(
+            "Packed",
+            6,
+            2,
+            Some(2),
+            None,
+            vec![field("a", 0, 2, Type::Short), field("b", 16, 4, Type::Int)],
+        ));
+        namespace.records.push(record_with_layout(
+            "Aligned",
+            16,
+            16,
+            None,
+            Some(16),
+            vec![field("a", 0, 4, Type::Int)],
+        ));
+        // A packed record containing `Aligned`, as emitted by the parser.
+        namespace.records.push(record_with_layout(
+            "PackedAligned",
+            18,
+            2,
+            Some(2),
+            None,
+            vec![field(
+                "__data",
+                0,
+                18,
+                Type::Array(9, Box::new(Type::Unsigned(2))),
+            )],
+        ));
+
+        let output = generate(Generator::default().layout_tests(true), &namespace);
+
+        assert!(
+            output.contains("#[repr(C, packed(2))]\n#[derive(Copy, Clone)]\npub struct Packed {")
+        );
+        assert!(output.contains("const _: () = assert!(::std::mem::offset_of!(Packed, b) == 2);"));
+        assert!(
+            output.contains("#[repr(C, align(16))]\n#[derive(Copy, Clone)]\npub struct Aligned {")
+        );
+        assert!(output.contains(
+            "#[repr(C, packed(2))]\n#[derive(Copy, Clone)]\npub struct PackedAligned {\n    pub __data: [u16; 9],\n}"
+        ));
+        assert!(
+            output.contains("const _: () = assert!(::std::mem::size_of::<PackedAligned>() == 18);")
+        );
+        assert_eq!(output.matches("align(").count(), 1);
+    }
+
+    #[test]
+    fn free_functions() {
+        let steinberg = vec![String::from("Steinberg")];
+        let factory = Type::Pointer {
+            is_const: false,
+            pointee: Box::new(Type::Record {
+                name: String::from("IPluginFactory"),
+                namespace: steinberg.clone(),
+            }),
+        };
+        let function = |name: &str, calling_convention, result_type| Function {
+            name: String::from(name),
+            doc: None,
+            link_name: None,
+            calling_convention,
+            arguments: Vec::new(),
+            result_type,
+            overload: None,
+        };
+
+        let mut namespace = Namespace::new();
+        namespace.functions.push(function(
+            "GetPluginFactory",
+            CallingConvention::Stdcall,
+            factory.clone(),
+        ));
+        namespace
+            .functions
+            .push(function("InitDll", CallingConvention::C, Type::Bool));
+        namespace
+            .functions
+            .push(function("ExitDll", CallingConvention::C, Type::Bool));
+
+        let mut inner = Namespace::new();
+        inner
+            .functions
+            .push(function("GetFactory", CallingConvention::C, factory));
+
+        let mut root = namespace;
+        root.children.insert(String::from("Steinberg"), inner);
+
+        let output = generate(Generator::default(), &root);
+
+        // Types declared in other namespaces are qualified
+        assert!(output.contains(
+            "pub type GetPluginFactory = unsafe extern \"stdcall\" fn() -> *mut Steinberg::IPluginFactory;\n"
+        ));
+        assert!(output.contains(
+            "extern \"stdcall\" {\n    pub fn GetPluginFactory(\n    ) -> *mut Steinberg::IPluginFactory;\n}\n"
+        ));
+        assert!(output.contains(
+            "extern \"C\" {\n    pub fn InitDll(\n    ) -> bool;\n    pub fn ExitDll(\n    ) -> bool;\n}\n"
+        ));
+        assert!(output
+            .contains("pub type GetFactory = unsafe extern \"C\" fn() -> *mut IPluginFactory;\n"));
+    }
+
+    #[test]
+    fn overloaded_free_functions() {
+        let function = |link_name: &str, arguments: Vec<Type>, overload| Function {
+            name: String::from("clamp"),
+            doc: None,
+            link_name: Some(String::from(link_name)),
+            calling_convention: CallingConvention::C,
+            arguments: arguments
+                .into_iter()
+                .map(|type_| Argument {
+                    name: String::from("value"),
+                    type_,
+                })
+                .collect(),
+            result_type: Type::Void,
+            overload,
+        };
+
+        let mut namespace = Namespace::new();
+        namespace
+            .functions
+            .push(function("_Z5clampi", vec![Type::Int], Some(0)));
+        namespace
+            .functions
+            .push(function("_Z5clampd", vec![Type::Double], Some(1)));
+
+        let output = generate(Generator::default(), &namespace);
+        assert!(output.contains("pub type clamp = "));
+        assert!(output.contains("pub type clamp_1 = "));
+        assert!(output.contains("    #[link_name = \"_Z5clampi\"]\n    pub fn clamp(\n"));
+        assert!(output.contains("    #[link_name = \"_Z5clampd\"]\n    pub fn clamp_1(\n"));
+
+        let generator = Generator::default().overload_naming(OverloadNaming::ArgumentTypes);
+        let output = generate(generator, &namespace);
+        assert!(output.contains("pub fn clamp_int(\n"));
+        assert!(output.contains("pub fn clamp_double(\n"));
+    }
+
+    #[test]
+    fn function_pointer_typedefs() {
+        let callback = |name: &str, calling_convention| {
+            let function = Type::Function {
+                calling_convention,
+                arguments: Vec::new(),
+                result_type: Box::new(Type::Bool),
+            };
+            let pointer = Type::Pointer {
+                is_const: false,
+                pointee: Box::new(function),
+            };
+            typedef(name, pointer)
+        };
+
+        let mut namespace = Namespace::new();
+        namespace
+            .typedefs
+            .push(callback("StdcallCallback", CallingConvention::Stdcall));
+        namespace
+            .typedefs
+            .push(callback("CCallback", CallingConvention::C));
+
+        let output = generate(Generator::default(), &namespace);
+
+        assert!(output.contains(
+            "pub type StdcallCallback = Option<unsafe extern \"stdcall\" fn() -> bool>;\n"
+        ));
+        assert!(output.contains("pub type CCallback = Option<unsafe extern \"C\" fn() -> bool>;\n"));
+    }
+
+    #[test]
+    fn iid_constants() {
+        let interface = |name: &str, iid| Record {
+            virtual_destructor: Some(VirtualDestructor {
+                position: 0,
+                abi: Abi::Itanium,
+                calling_convention: CallingConvention::C,
+            }),
+            iid,
+            ..interface(name, Vec::new())
+        };
+
+        let mut iid = [0; 16];
+        iid[0] = 0xf2;
+        iid[15] = 0x01;
+
+        let mut namespace = Namespace::new();
+        namespace.records.push(interface("IFoo", Some(iid)));
+        namespace.records.push(interface("IBar", None));
+
+        let generator = interface_generator()
+            .iid_generator(|name| format!("{name}_guid()"))
+            .iid_constant_type("TUID");
+        let output = generate(generator, &namespace);
+
+        assert!(output.contains(
+            "pub const IFoo_iid: TUID = [0xf2u8 as _, 0x00u8 as _, 0x00u8 as _, 0x00u8 as _, \
+             0x00u8 as _, 0x00u8 as _, 0x00u8 as _, 0x00u8 as _, 0x00u8 as _, 0x00u8 as _, \
+             0x00u8 as _, 0x00u8 as _, 0x00u8 as _, 0x00u8 as _, 0x00u8 as _, 0x01u8 as _];\n"
+        ));
+        // Interfaces without a declared IID don't get a constant.
+        assert!(!output.contains("IBar_iid"));
+    }
+
+    #[test]
+    fn interface_layout_tests() {
+        let mut namespace = Namespace::new();
+        namespace
+            .records
+            .push(interface("IFoo", vec![method("foo")]));
+
+        namespace.records.push(Record {
+            bases: vec![Base {
+                name: String::from("IFoo"),
+                offset: 0,
+                bases: Vec::new(),
+            }],
+            virtual_destructor: Some(VirtualDestructor {
+                position: 0,
+                abi: Abi::Itanium,
+                calling_convention: CallingConvention::C,
+            }),
+            ..interface("IBar", vec![method("bar"), method("baz")])
+        });
+        namespace.records.push(Record {
+            virtual_destructor: Some(VirtualDestructor {
+                position: 1,
+                abi: Abi::Msvc,
+                calling_convention: CallingConvention::Thiscall,
+            }),
+            ..interface("IBaz", vec![method("baz")])
+        });
+
+        let output = generate(interface_generator().layout_tests(true), &namespace);
+
+        assert!(output.contains(
+            "const _: () = assert!(::std::mem::size_of::<IFooVtbl>() == ::std::mem::size_of::<[*const (); 1]>());\n"
+        ));
+        assert!(output.contains(
+            "const _: () = assert!(::std::mem::size_of::<IBarVtbl>() == ::std::mem::size_of::<IFooVtbl>() + ::std::mem::size_of::<[*const (); 4]>());\n"
+        ));
+        assert!(output.contains(
+            "const _: () = assert!(::std::mem::size_of::<IBazVtbl>() == ::std::mem::size_of::<[*const (); 2]>());\n"
+        ));
+    }
+
+    #[test]
+    fn destructor_slots() {
+        for (abi, calling_convention, slots, thunk) in [
+            (Abi::Itanium, CallingConvention::C, "pub __destructor: unsafe extern \"C\" fn(this: *mut IFoo),\n    pub __deleting_destructor: unsafe extern \"C\" fn(this: *mut IFoo),\n", "unsafe extern \"C\" fn __deleting_destructor<"),
+            (Abi::Msvc, CallingConvention::Thiscall, "pub __deleting_destructor: unsafe extern \"thiscall\" fn(this: *mut IFoo, flags: ::std::ffi::c_uint) -> *mut ::std::ffi::c_void,\n", "unsafe extern \"thiscall\" fn __deleting_destructor<"),
+        ] {
+            let mut namespace = Namespace::new();
+            namespace.records.push(Record {
+                virtual_destructor: Some(VirtualDestructor {
+                    position: 1,
+                    abi,
+                    calling_convention,
+                }),
+                ..interface("IFoo", vec![method("first"), method("second")])
+            });
+
+            let output = generate(interface_generator(), &namespace);
+
+            let first = output.find("pub first: ").unwrap();
+            let destructor = output.find(slots).unwrap();
+            let second = output.find("pub second: ").unwrap();
+            assert!(first < destructor && destructor < second);
+            assert!(output.contains(thunk));
+            assert!(output.contains("__deleting_destructor: __deleting_destructor::<C, W, O, ADJUST>,"));
+        }
+    }
+
+    #[test]
+    fn indirect_result() {
+        let get_rect = |indirect_result| Method {
+            arguments: vec![Argument {
+                name: String::from("index"),
+                type_: Type::Int,
+            }],
+            result_type: Type::Record {
+                name: String::from("Rect"),
+                namespace: Vec::new(),
+            },
+            indirect_result: Some(indirect_result),
+            ..method("getRect")
+        };
+
+        for (indirect_result, receiver, call) in [
+            (
+                IndirectResult::AfterThis,
+                "this: *mut IFoo,\n        __result: *mut Rect,\n",
+                "ptr,\n            result.as_mut_ptr(),\n",
+            ),
+            (
+                IndirectResult::BeforeThis,
+                "__result: *mut Rect,\n        this: *mut IFoo,\n",
+                "result.as_mut_ptr(),\n            ptr,\n",
+            ),
+        ] {
+            let mut namespace = Namespace::new();
+            namespace
+                .records
+                .push(interface("IFoo", vec![get_rect(indirect_result)]));
+
+            let output = generate(interface_generator(), &namespace);
+
+            // The vtable entry takes and returns the hidden result pointer
+            assert!(output.contains(&format!(
+                "pub getRect: unsafe extern \"system\" fn(\n        {receiver}        index: ::std::ffi::c_int,\n    ) -> *mut Rect,"
+            )));
+            // The trait keeps the by-value signature
+            assert!(output.contains(
+                "unsafe fn getRect(\n        &self,\n        index: ::std::ffi::c_int,\n    ) -> Rect;"
+            ));
+            assert!(output.contains(call));
+            assert!(output.contains("result.assume_init()"));
+            assert!(output.contains("__result.write((*ptr).getRect("));
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ir::Namespace;
+
+/// The definitions parsed for several targets, split into those shared by every target and
+/// those which differ between targets.
+pub(crate) struct TargetNamespace {
+    /// Definitions which are the same for every target. Has no children.
+    pub common: Namespace,
+    /// Definitions which differ between targets, along with the indices of the targets they
+    /// belong to. Targets with identical definitions share a group. None of the namespaces have
+    /// children.
+    pub groups: Vec<(Vec<usize>, Namespace)>,
+    /// Nested namespaces, by name.
+    pub children: BTreeMap<String, TargetNamespace>,
+}
+
+impl TargetNamespace {
+    /// Splits `namespaces`, which contains the global namespace for each target.
+    pub fn split(namespaces: &[&Namespace]) -> TargetNamespace {
+        let mut common = Namespace::new();
+        let mut divergent = vec![Namespace::new(); namespaces.len()];
+
+        macro_rules! split_items {
+            ($field:ident) => {
+                let lists = namespaces.iter().map(|ns| &ns.$field).collect::<Vec<_>>();
+                let outputs = divergent.iter_mut().map(|ns| &mut ns.$field);
+                split_items(&lists, &mut common.$field, outputs);
+            };
+        }
+
+        split_items!(typedefs);
+        split_items!(records);
+        split_items!(opaque_records);
+        split_items!(functions);
+        split_items!(constants);
+        split_items!(unparsed_constants);
+
+        let mut groups: Vec<(Vec<usize>, Namespace)> = Vec::new();
+        for (index, namespace) in divergent.into_iter().enumerate() {
+            if namespace.is_empty() {
+                continue;
+            }
+
+            if let Some(group) = groups.iter_mut().find(|(_, ns)| *ns == namespace) {
+                group.0.push(index);
+            } else {
+                groups.push((vec![index], namespace));
+            }
+        }
+
+        let names = namespaces
+            .iter()
+            .flat_map(|ns| ns.children.keys())
+            .collect::<BTreeSet<_>>();
+
+        let empty = Namespace::new();
+        let mut children = BTreeMap::new();
+        for name in names {
+            let child_namespaces = namespaces
+                .iter()
+                .map(|ns| ns.children.get(name).unwrap_or(&empty))
+                .collect::<Vec<_>>();
+            children.insert(name.clone(), TargetNamespace::split(&child_namespaces));
+        }
+
+        TargetNamespace {
+            common,
+            groups,
+            children,
+        }
+    }
+
+    /// Returns `true` if the namespace and all of its children contain no definitions.
+    pub fn is_empty(&self) -> bool {
+        self.common.is_empty()
+            && self.groups.is_empty()
+            && self.children.values().all(|child| child.is_empty())
+    }
+}
+
+/// Moves the items present in every one of `lists` to `common`, and the remaining items of each
+/// list to the corresponding element of `outputs`.
+fn split_items<'a, T, I>(lists: &[&Vec<T>], common: &mut Vec<T>, outputs: I)
+where
+    T: Clone + PartialEq + 'a,
+    I: Iterator<Item = &'a mut Vec<T>>,
+{
+    if let Some((first, rest)) = lists.split_first() {
+        for item in first.iter() {
+            if rest.iter().all(|list| list.contains(item)) {
+                common.push(item.clone());
+            }
+        }
+    }
+
+    for (list, output) in lists.iter().zip(outputs) {
+        for item in list.iter() {
+            if !common.contains(item) {
+                output.push(item.clone());
+            }
+        }
+    }
+}
+
+/// Returns a `cfg` predicate which matches the `rustc` target `target`.
+pub(crate) fn target_cfg(target: &str) -> String {
+    let parts = target.split('-').collect::<Vec<_>>();
+
+    let arch = match parts[0] {
+        "i386" | "i586" | "i686" => "x86",
+        "arm64" | "arm64e" => "aarch64",
+        arch if arch.starts_with("arm") || arch.starts_with("thumb") => "arm",
+        arch => arch,
+    };
+
+    let os = if parts.contains(&"android") || parts.contains(&"androideabi") {
+        "android"
+    } else if parts.contains(&"darwin") || parts.contains(&"macos") {
+        "macos"
+    } else if parts.contains(&"windows") {
+        "windows"
+    } else if parts.contains(&"linux") {
+        "linux"
+    } else {
+        parts.get(2).or(parts.get(1)).copied().unwrap_or("none")
+    };
+
+    let env = match parts.last().copied().unwrap_or("") {
+        env if env.starts_with("msvc") => Some("msvc"),
+        env if env.starts_with("gnu") => Some("gnu"),
+        env if env.starts_with("musl") => Some("musl"),
+        _ => None,
+    };
+
+    let mut cfg = format!("target_arch = \"{arch}\", target_os = \"{os}\"");
+    if let Some(env) = env {
+        cfg.push_str(&format!(", target_env = \"{env}\""));
+    }
+
+    format!("all({cfg})")
+}
+
+/// Returns a `cfg` predicate which matches any of `targets`.
+pub(crate) fn targets_cfg<'a, I: IntoIterator<Item = &'a str>>(targets: I) -> String {
+    let cfgs = targets.into_iter().map(target_cfg).collect::<Vec<_>>();
+    if cfgs.len() == 1 {
+        cfgs.into_iter().next().unwrap()
+    } else {
+        format!("any({})", cfgs.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{Constant, Type, Value};
+
+    fn constant(name: &str, value: i64) -> Constant {
+        Constant {
+            name: String::from(name),
+            doc: None,
+            type_: Type::Int,
+            value: Value::Signed(value),
+        }
+    }
+
+    #[test]
+    fn split_namespaces() {
+        let mut windows = Namespace::new();
+        windows.constants.push(constant("kShared", 1));
+        windows.constants.push(constant("kPlatform", 2));
+
+        let mut linux = Namespace::new();
+        linux.constants.push(constant("kShared", 1));
+        linux.constants.push(constant("kPlatform", 3));
+        let mut child = Namespace::new();
+        child.constants.push(constant("kLinuxOnly", 4));
+        linux.children.insert(String::from("ns"), child);
+
+        let macos = linux.clone();
+
+        let split = TargetNamespace::split(&[&windows, &linux, &macos]);
+
+        assert_eq!(split.common.constants, [constant("kShared", 1)]);
+        assert_eq!(split.groups.len(), 2);
+        assert_eq!(split.groups[0].0, [0]);
+        assert_eq!(split.groups[0].1.constants, [constant("kPlatform", 2)]);
+        assert_eq!(split.groups[1].0, [1, 2]);
+        assert_eq!(split.groups[1].1.constants, [constant("kPlatform", 3)]);
+
+        let child = &split.children["ns"];
+        assert!(child.common.is_empty());
+        assert_eq!(child.groups.len(), 1);
+        assert_eq!(child.groups[0].0, [1, 2]);
+    }
+
+    #[test]
+    fn cfg_predicates() {
+        assert_eq!(
+            target_cfg("x86_64-pc-windows-msvc"),
+            "all(target_arch = \"x86_64\", target_os = \"windows\", target_env = \"msvc\")"
+        );
+        assert_eq!(
+            target_cfg("i686-pc-windows-msvc"),
+            "all(target_arch = \"x86\", target_os = \"windows\", target_env = \"msvc\")"
+        );
+        assert_eq!(
+            target_cfg("aarch64-apple-darwin"),
+            "all(target_arch = \"aarch64\", target_os = \"macos\")"
+        );
+        assert_eq!(
+            target_cfg("aarch64-unknown-linux-gnu"),
+            "all(target_arch = \"aarch64\", target_os = \"linux\", target_env = \"gnu\")"
+        );
+        assert_eq!(
+            targets_cfg(["x86_64-apple-darwin", "aarch64-linux-android"]),
+            "any(all(target_arch = \"x86_64\", target_os = \"macos\"), \
+             all(target_arch = \"aarch64\", target_os = \"android\"))"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Builders for the IR used by unit tests.
+
+use crate::ir::{Field, Method, Namespace, Record, RecordKind, Type, Typedef};
+use crate::{Error, Generator};
+
+/// Returns an 8-byte struct with no fields, bases, or virtual methods.
+pub fn record(name: &str) -> Record {
+    Record {
+        name: String::from(name),
+        doc: None,
+        kind: RecordKind::Struct,
+        size: 8,
+        align: 8,
+        packed: None,
+        explicit_align: None,
+        fields: Vec::new(),
+        bases: Vec::new(),
+        virtual_methods: Vec::new(),
+        virtual_destructor: None,
+        iid: None,
+        inner: Namespace::new(),
+    }
+}
+
+/// Returns an interface with the given virtual methods and no destructor.
+pub fn interface(name: &str, virtual_methods: Vec<Method>) -> Record {
+    Record {
+        virtual_methods,
+        iid: Some([0; 16]),
+        ..record(name)
+    }
+}
+
+/// Returns a method with no arguments returning `void`.
+pub fn method(name: &str) -> Method {
+    Method {
+        name: String::from(name),
+        doc: None,
+        arguments: Vec::new(),
+        result_type: Type::Void,
+        overload: None,
+        indirect_result: None,
+    }
+}
+
+/// Returns a field which isn't a bitfield.
+pub fn field(name: &str, bit_offset: usize, size: usize, type_: Type) -> Field {
+    Field {
+        name: Some(String::from(name)),
+        doc: None,
+        bit_offset: Some(bit_offset),
+        size,
+        bitfield: None,
+        type_,
+    }
+}
+
+pub fn typedef(name: &str, type_: Type) -> Typedef {
+    Typedef {
+        name: String::from(name),
+        doc: None,
+        type_,
+        inner: Namespace::new(),
+    }
+}
+
+/// Generates bindings for `namespace` with `generator`, which must succeed.
+pub fn generate(generator: Generator, namespace: &Namespace) -> String {
+    let mut output = Vec::new();
+    generator.generate_from_ir(namespace, &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+/// Parses `source` for `target`. Returns `None` if libclang can't be loaded, in which case the
+/// calling test should be skipped.
+pub fn parse(target: &str, source: &str) -> Option<Result<Namespace, Error>> {
+    match Generator::default().target(target).parse(source) {
+        Err(Error::ClangLoad(message)) => {
+            eprintln!("skipping test: unable to load libclang: {message}");
+            None
+        }
+        result => Some(result.map(|(namespace, _)| namespace)),
+    }
+}
+
+/// Returns the record named `name` in `namespace`.
+pub fn find_record<'a>(namespace: &'a Namespace, name: &str) -> &'a Record {
+    namespace
+        .records
+        .iter()
+        .find(|record| record.name == name)
+        .unwrap_or_else(|| panic!("record `{name}` not found"))
+}
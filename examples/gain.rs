@@ -583,10 +583,25 @@ extern "system" fn ModuleExit() -> bool {
     true
 }
 
-#[no_mangle]
-extern "system" fn GetPluginFactory() -> *mut IPluginFactory {
+fn plugin_factory() -> *mut IPluginFactory {
     ComWrapper::new(Factory {})
         .to_com_ptr::<IPluginFactory>()
         .unwrap()
         .into_raw()
 }
+
+// `GetPluginFactory` is declared `PLUGIN_API`, which is `__stdcall` on 32-bit Windows.
+#[cfg(all(target_os = "windows", target_arch = "x86"))]
+#[no_mangle]
+extern "stdcall" fn GetPluginFactory() -> *mut IPluginFactory {
+    plugin_factory()
+}
+
+#[cfg(not(all(target_os = "windows", target_arch = "x86")))]
+#[no_mangle]
+extern "C" fn GetPluginFactory() -> *mut IPluginFactory {
+    plugin_factory()
+}
+
+// Check that the entry point's signature matches the declaration in the SDK headers.
+const _: vst3_bindgen::GetPluginFactory = GetPluginFactory;
@@ -6,10 +6,34 @@
 //! # Bindings
 //!
 //! Generated bindings are located in the [`Steinberg`] module. In addition to the COM interfaces,
-//! bindings include struct definitions, type aliases, constants, and enums. The module structure
-//! of the bindings mirrors the namespace structure of the original headers, with minor differences
-//! where necessary (e.g., definitions which are nested inside a C++ type `SomeType` will be found
-//! inside a `SomeType_` module in the generated bindings).
+//! bindings include struct definitions, type aliases, constants, enums, and function declarations.
+//! The module structure of the bindings mirrors the namespace structure of the original headers,
+//! with minor differences where necessary (e.g., definitions which are nested inside a C++ type
+//! `SomeType` will be found inside a `SomeType_` module in the generated bindings). Definitions in
+//! the global namespace, such as macro constants and the `GetPluginFactory` entry point, are
+//! located at the crate root.
+//!
+//! For each function declaration, the bindings include both an `extern` declaration and a type
+//! alias of the same name for the corresponding function pointer type. Both use the calling
+//! convention of the C++ declaration on the target, e.g. `GetPluginFactory` is `extern "stdcall"`
+//! on 32-bit Windows and `extern "C"` elsewhere. Plugins can use the type aliases to check the
+//! signatures of their exported entry points:
+//!
+//! ```ignore
+//! #[cfg(all(target_os = "windows", target_arch = "x86"))]
+//! #[no_mangle]
+//! extern "stdcall" fn GetPluginFactory() -> *mut IPluginFactory {
+//!     // ...
+//! }
+//!
+//! #[cfg(not(all(target_os = "windows", target_arch = "x86")))]
+//! #[no_mangle]
+//! extern "C" fn GetPluginFactory() -> *mut IPluginFactory {
+//!     // ...
+//! }
+//!
+//! const _: vst3_bindgen::GetPluginFactory = GetPluginFactory;
+//! ```
 //!
 //! For each COM interface `IInterface` in the C++ headers, the bindings include a corresponding
 //! Rust type `IInterface`, a virtual table struct `IInterfaceVtbl`, and a trait `IInterfaceTrait`
@@ -65,32 +89,11 @@ use std::ffi::c_void;
 
 use Steinberg::{int8, kNoInterface, kResultOk, tresult, uint32, FUnknown, FUnknownVtbl, TUID};
 
-use com_scrape_types::{Construct, Guid, Header, InterfaceList, Wrapper};
+use com_scrape_types::{Construct, Guid, Header, InterfaceList, Offset, Wrapper};
 
 pub use com_scrape_types;
 pub use com_scrape_types::{Class, ComPtr, ComRef, ComWrapper, Interface};
 
-const fn tuid_as_guid(tuid: TUID) -> Guid {
-    [
-        tuid[0] as u8,
-        tuid[1] as u8,
-        tuid[2] as u8,
-        tuid[3] as u8,
-        tuid[4] as u8,
-        tuid[5] as u8,
-        tuid[6] as u8,
-        tuid[7] as u8,
-        tuid[8] as u8,
-        tuid[9] as u8,
-        tuid[10] as u8,
-        tuid[11] as u8,
-        tuid[12] as u8,
-        tuid[13] as u8,
-        tuid[14] as u8,
-        tuid[15] as u8,
-    ]
-}
-
 #[inline]
 unsafe fn FUnknown_query_interface(this: *mut c_void, iid: &Guid) -> Option<*mut c_void> {
     let ptr = this as *mut FUnknown;
@@ -117,12 +120,13 @@ unsafe fn FUnknown_release(this: *mut c_void) -> usize {
 }
 
 impl FUnknown {
-    const fn make_vtbl<C, W, const OFFSET: isize>() -> FUnknownVtbl
+    const fn make_vtbl<C, W, O, const ADJUST: isize>() -> FUnknownVtbl
     where
         C: Class,
         W: Wrapper<C>,
+        O: Offset,
     {
-        unsafe extern "system" fn queryInterface<C, W, const OFFSET: isize>(
+        unsafe extern "system" fn queryInterface<C, W, O, const ADJUST: isize>(
             this: *mut FUnknown,
             _iid: *const TUID,
             obj: *mut *mut c_void,
@@ -130,8 +134,9 @@ impl FUnknown {
         where
             C: Class,
             W: Wrapper<C>,
+            O: Offset,
         {
-            let header_ptr = (this as *mut u8).offset(-OFFSET) as *mut Header<C>;
+            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;
             if let Some(result) = C::Interfaces::query(&*(_iid as *const Guid)) {
                 let ptr = W::data_from_header(header_ptr);
                 W::add_ref(ptr);
@@ -144,41 +149,48 @@ impl FUnknown {
             }
         }
 
-        unsafe extern "system" fn addRef<C, W, const OFFSET: isize>(this: *mut FUnknown) -> uint32
+        unsafe extern "system" fn addRef<C, W, O, const ADJUST: isize>(
+            this: *mut FUnknown,
+        ) -> uint32
         where
             C: Class,
             W: Wrapper<C>,
+            O: Offset,
         {
-            let header_ptr = (this as *mut u8).offset(-OFFSET) as *mut Header<C>;
+            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;
             let ptr = W::data_from_header(header_ptr);
             W::add_ref(ptr) as uint32
         }
 
-        unsafe extern "system" fn release<C, W, const OFFSET: isize>(this: *mut FUnknown) -> uint32
+        unsafe extern "system" fn release<C, W, O, const ADJUST: isize>(
+            this: *mut FUnknown,
+        ) -> uint32
         where
             C: Class,
             W: Wrapper<C>,
+            O: Offset,
         {
-            let header_ptr = (this as *mut u8).offset(-OFFSET) as *mut Header<C>;
+            let header_ptr = (this as *mut u8).offset(-O::OFFSET - ADJUST) as *mut Header<C>;
             let ptr = W::data_from_header(header_ptr);
             W::release(ptr) as uint32
         }
 
         FUnknownVtbl {
-            queryInterface: queryInterface::<C, W, OFFSET>,
-            addRef: addRef::<C, W, OFFSET>,
-            release: release::<C, W, OFFSET>,
+            queryInterface: queryInterface::<C, W, O, ADJUST>,
+            addRef: addRef::<C, W, O, ADJUST>,
+            release: release::<C, W, O, ADJUST>,
         }
     }
 }
 
-unsafe impl<C, W, const OFFSET: isize> Construct<C, W, OFFSET> for FUnknown
+unsafe impl<C, W, O> Construct<C, W, O> for FUnknown
 where
     C: Class,
     W: Wrapper<C>,
+    O: Offset,
 {
     const OBJ: FUnknown = FUnknown {
-        vtbl: &Self::make_vtbl::<C, W, OFFSET>(),
+        vtbl: &Self::make_vtbl::<C, W, O, 0>(),
     };
 }
 